@@ -231,6 +231,11 @@ pub fn follows<'a, 'b>() -> clap::App<'a, 'b> {
         .about("Returns the latest updates of followed manga")
 }
 
+pub fn queue<'a, 'b>() -> clap::App<'a, 'b> {
+    clap::SubCommand::with_name("queue")
+        .about("Shows the downloads queued and running in each priority class")
+}
+
 pub fn manga_add<'a, 'b>() -> clap::App<'a, 'b> {
     clap::SubCommand::with_name("add")
         .about("Adds a manga")
@@ -277,11 +282,47 @@ pub fn manga_unfollow<'a, 'b>() -> clap::App<'a, 'b> {
             .validator(id_validator))
 }
 
+fn chapter_range_validator(string: String) -> Result<(), String> {
+    match mangadex_fs::store::parse_chapter_range(&string) {
+        Some(_) => Ok(()),
+        None => Err(format!("invalid chapter range: \"{}\"", string))
+    }
+}
+
+pub fn manga_pin<'a, 'b>() -> clap::App<'a, 'b> {
+    clap::SubCommand::with_name("pin")
+        .about("Downloads given manga to the disk, to be read when MangaDex can't be reached")
+        .arg(clap::Arg::with_name("manga_id")
+            .help("ID of the manga")
+            .takes_value(true)
+            .required(true)
+            .validator(id_validator))
+        .arg(clap::Arg::with_name("chapters")
+            .help("Chapter numbers to pin, e.g. \"1-20\" or \"5\". All chapters in the configured languages by default")
+            .takes_value(true)
+            .required(false)
+            .short("c")
+            .long("chapters")
+            .validator(chapter_range_validator))
+}
+
+pub fn manga_unpin<'a, 'b>() -> clap::App<'a, 'b> {
+    clap::SubCommand::with_name("unpin")
+        .about("Removes given manga from the disk")
+        .arg(clap::Arg::with_name("manga_id")
+            .help("ID of the manga")
+            .takes_value(true)
+            .required(true)
+            .validator(id_validator))
+}
+
 pub fn manga<'a, 'b>() -> clap::App<'a, 'b> {
     clap::SubCommand::with_name("manga")
         .subcommand(manga_add())
         .subcommand(manga_follow())
         .subcommand(manga_unfollow())
+        .subcommand(manga_pin())
+        .subcommand(manga_unpin())
 }
 
 pub fn mdlist_show<'a, 'b>() -> clap::App<'a, 'b> {
@@ -356,6 +397,7 @@ pub fn client<'a, 'b>() -> clap::App<'a, 'b> {
         .subcommand(search()) 
         .subcommand(chapter())
         .subcommand(follows())
+        .subcommand(queue())
         .subcommand(manga())
         .subcommand(mdlist())
 }
\ No newline at end of file
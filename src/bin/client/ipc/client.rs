@@ -134,4 +134,36 @@ impl Client {
             _ => Err(ClientError::Daemon("unexpected daemon response".into()))
         }
     }
+
+    pub async fn queue_status(&mut self) -> ClientResult<Vec<mangadex_fs::scheduler::QueueStatus>> {
+        ipc::Command::QueueStatus.ipc_send(&mut self.stream).await.map_err(ClientError::IO)?;
+
+        match ipc::Response::ipc_try_receive(&mut self.stream).await.map_err(ClientError::IO)? {
+            Some(ipc::Response::QueueStatus(queue_status)) => Ok(queue_status),
+            _ => Err(ClientError::Daemon("unexpected daemon response".into()))
+        }
+    }
+
+    pub async fn pin_manga<F: FnMut(u64, u64)>(&mut self, id: u64, chapters: Option<String>, mut progress: F) -> ClientResult<()> {
+        ipc::Command::PinManga(id, chapters).ipc_send(&mut self.stream).await.map_err(ClientError::IO)?;
+
+        loop {
+            match ipc::Response::ipc_try_receive(&mut self.stream).await.map_err(ClientError::IO)? {
+                Some(ipc::Response::PinProgress(pinned, total)) => progress(pinned, total),
+                Some(ipc::Response::PinManga(Ok(_))) => return Ok(()),
+                Some(ipc::Response::PinManga(Err(failure))) => return Err(ClientError::Daemon(failure)),
+                _ => return Err(ClientError::Daemon("unexpected daemon response".into()))
+            }
+        }
+    }
+
+    pub async fn unpin_manga(&mut self, id: u64) -> ClientResult<()> {
+        ipc::Command::UnpinManga(id).ipc_send(&mut self.stream).await.map_err(ClientError::IO)?;
+
+        match ipc::Response::ipc_try_receive(&mut self.stream).await.map_err(ClientError::IO)? {
+            Some(ipc::Response::UnpinManga(Ok(_))) => Ok(()),
+            Some(ipc::Response::UnpinManga(Err(failure))) => Err(ClientError::Daemon(failure)),
+            _ => Err(ClientError::Daemon("unexpected daemon response".into()))
+        }
+    }
 }
\ No newline at end of file
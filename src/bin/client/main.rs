@@ -255,6 +255,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         }
                     },
                     ("unfollow", Some(unfollow_args)) => client.unfollow_manga(unfollow_args.value_of("manga_id").unwrap().parse::<u64>().unwrap()).await,
+                    ("pin", Some(pin_args)) => client.pin_manga(
+                        pin_args.value_of("manga_id").unwrap().parse::<u64>().unwrap(),
+                        pin_args.value_of("chapters").map(String::from),
+                        |pinned, total| println!("Pinned {}/{} chapters", pinned.to_string().white(), total)
+                    ).await,
+                    ("unpin", Some(unpin_args)) => client.unpin_manga(unpin_args.value_of("manga_id").unwrap().parse::<u64>().unwrap()).await,
                     (command, _) => Err(ipc::ClientError::Client(format!("unknown subcommand \"manga {}\"", command)))
                 },
                 ("mdlist", Some(mdlist_args)) => match mdlist_args.subcommand() {
@@ -398,6 +404,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         }
                     })
                 },
+                ("queue", Some(_)) => {
+                    client.queue_status().await.map(|queue_status| {
+                        for class in &queue_status {
+                            println!(
+                                "{priority:<11} {0} {queued:>4} queued {0} {active:>4} active",
+                                "│".bright_black(),
+                                priority = class.priority.to_string().white(),
+                                queued = class.queued,
+                                active = class.active
+                            );
+                        }
+                    })
+                },
                 (command, _) => Err(ipc::ClientError::Client(format!("unknown subcommand \"{}\"", command)))
             };
 
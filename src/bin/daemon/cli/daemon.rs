@@ -1,3 +1,14 @@
+fn id_validator(string: String) -> Result<(), String> {
+    match string.parse::<u32>() {
+        Err(e) => Err(e.to_string()),
+        _ => Ok(())
+    }
+}
+
+fn mode_validator(string: String) -> Result<(), String> {
+    mangadex_fs::cfg::parse_mode(&string).map(|_| ())
+}
+
 pub fn daemon<'a, 'b>() -> clap::App<'a, 'b> {
     clap::App::new(format!("{} - daemon", env!("CARGO_PKG_NAME")))
         .arg(
@@ -6,4 +17,33 @@ pub fn daemon<'a, 'b>() -> clap::App<'a, 'b> {
                 .value_name("mount point")
                 .help("Mount filesystem at this path")
         )
+        .arg(clap::Arg::with_name("allow_other")
+            .help("Allow other users to access the mount (needs user_allow_other in /etc/fuse.conf)")
+            .long("allow-other"))
+        .arg(clap::Arg::with_name("default_permissions")
+            .help("Let the kernel check permissions against the file modes")
+            .long("default-permissions"))
+        .arg(clap::Arg::with_name("fsname")
+            .help("Filesystem name shown by mount")
+            .long("fsname")
+            .takes_value(true))
+        .arg(clap::Arg::with_name("subtype")
+            .help("Filesystem subtype shown by mount")
+            .long("subtype")
+            .takes_value(true))
+        .arg(clap::Arg::with_name("uid")
+            .help("Owner of every file, defaults to the daemon's user")
+            .long("uid")
+            .takes_value(true)
+            .validator(id_validator))
+        .arg(clap::Arg::with_name("gid")
+            .help("Group of every file, defaults to the daemon's group")
+            .long("gid")
+            .takes_value(true)
+            .validator(id_validator))
+        .arg(clap::Arg::with_name("mode")
+            .help("Octal permissions of files, directories also get execute permission where readable")
+            .long("mode")
+            .takes_value(true)
+            .validator(mode_validator))
 }
\ No newline at end of file
@@ -34,7 +34,10 @@ impl Connection {
                         ipc::Command::UnfollowManga(id) => self.unfollow(id).await?,
                         ipc::Command::MarkChapterRead(id) => self.mark_chapter_read(id).await?,
                         ipc::Command::MarkChapterUnread(id) => self.mark_chapter_unread(id).await?,
-                        ipc::Command::Follows => self.follows().await?
+                        ipc::Command::Follows => self.follows().await?,
+                        ipc::Command::QueueStatus => self.queue_status().await?,
+                        ipc::Command::PinManga(id, chapters) => self.pin_manga(id, chapters).await?,
+                        ipc::Command::UnpinManga(id) => self.unpin_manga(id).await?
                     };
 
                     response.ipc_send(&mut self.stream).await?;
@@ -235,4 +238,65 @@ impl Connection {
             }
         })
     }
+
+    pub async fn queue_status(&mut self) -> std::io::Result<ipc::Response> {
+        Ok(ipc::Response::QueueStatus(self.context.queue_status()))
+    }
+
+    // Sends a `PinProgress` after every chapter, then the result.
+    pub async fn pin_manga(&mut self, id: u64, chapters: Option<String>) -> std::io::Result<ipc::Response> {
+        let range = match chapters.as_deref().map(mangadex_fs::store::parse_chapter_range) {
+            Some(None) => return Ok(ipc::Response::PinManga(Err("invalid chapter range".into()))),
+            Some(range) => range,
+            None => None
+        };
+
+        let chapter_ids = match self.context.pin_manga(id, range).await {
+            Ok(chapter_ids) => chapter_ids,
+            Err(error) => return Ok(ipc::Response::PinManga(Err(pin_error(id, error))))
+        };
+
+        // Nothing is recorded as pinned until a chapter is stored.
+        if chapter_ids.is_empty() {
+            return Ok(ipc::Response::PinManga(Err("no chapters to pin".into())));
+        }
+
+        info!("pinning {} chapters of manga {}", chapter_ids.len(), id);
+
+        for (index, chapter_id) in chapter_ids.iter().enumerate() {
+            if let Err(error) = self.context.pin_chapter(id, *chapter_id).await {
+                return Ok(ipc::Response::PinManga(Err(pin_error(id, error))));
+            }
+
+            ipc::Response::PinProgress(index as u64 + 1u64, chapter_ids.len() as u64).ipc_send(&mut self.stream).await?;
+        }
+
+        Ok(ipc::Response::PinManga(Ok(())))
+    }
+
+    pub async fn unpin_manga(&mut self, id: u64) -> std::io::Result<ipc::Response> {
+        Ok(match self.context.unpin_manga(id).await {
+            Ok(true) => {
+                info!("unpinned manga {}", id);
+
+                ipc::Response::UnpinManga(Ok(()))
+            },
+            Ok(false) => ipc::Response::UnpinManga(Err("manga is not pinned".into())),
+            Err(error) => {
+                warn!("unpin manga {} error: {:?}", id, error);
+
+                ipc::Response::UnpinManga(Err(format!("storage error: {}", error)))
+            }
+        })
+    }
+}
+
+fn pin_error(id: u64, error: mangadex_fs::store::PinError) -> String {
+    warn!("pin manga {} error: {:?}", id, error);
+
+    match error {
+        mangadex_fs::store::PinError::Request(_) => "request error".into(),
+        mangadex_fs::store::PinError::IO(error) => format!("storage error: {}", error),
+        mangadex_fs::store::PinError::Incomplete => "a page failed to download".into()
+    }
 }
\ No newline at end of file
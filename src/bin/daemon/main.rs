@@ -17,7 +17,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     info!("consider supporting MangaDex at https://mangadex.org/support");
 
-    let config = if mangadex_fs::cfg::config_file_path().exists() {
+    let mut config = if mangadex_fs::cfg::config_file_path().exists() {
         let maybe_config = mangadex_fs::cfg::Config::load().await?;
 
         match maybe_config {
@@ -34,8 +34,24 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         mangadex_fs::cfg::Config::default()
     };
 
+    if cli.is_present("allow_other") { config.allow_other = true; }
+    if cli.is_present("default_permissions") { config.default_permissions = true; }
+    if let Some(fsname) = cli.value_of("fsname") { config.fsname = fsname.to_owned(); }
+    if let Some(subtype) = cli.value_of("subtype") { config.subtype = subtype.to_owned(); }
+    if let Some(uid) = cli.value_of("uid") { config.uid = uid.parse::<u32>().ok(); }
+    if let Some(gid) = cli.value_of("gid") { config.gid = gid.parse::<u32>().ok(); }
+    if let Some(mode) = cli.value_of("mode") {
+        match mangadex_fs::cfg::parse_mode(mode) {
+            Ok(mode) => config.mode = Some(mode),
+            Err(error) => {
+                error!("{}", error);
+                return Ok(())
+            }
+        }
+    }
+
     let socket_directory = config.socket.parent().unwrap();
-    let mountpoint = match cli.value_of("mountpoint").map(Into::into).or(config.mountpoint) {
+    let mountpoint = match cli.value_of("mountpoint").map(Into::into).or(config.mountpoint.clone()) {
         Some(mountpoint) => mountpoint,
         None => {
             error!("mountpoint not present either in passed arguments or config file");
@@ -56,12 +72,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             let mut sigint = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::interrupt())?;
             let mut sigterm = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
 
-            let uid = nix::unistd::Uid::current();
-            let gid = nix::unistd::Gid::current();
+            let uid = config.uid.map(nix::unistd::Uid::from_raw).unwrap_or_else(nix::unistd::Uid::current);
+            let gid = config.gid.map(nix::unistd::Gid::from_raw).unwrap_or_else(nix::unistd::Gid::current);
 
-            let mut polyfuse_server = polyfuse_tokio::Server::mount(mountpoint, &[]).await?;
+            let mount_options = config.mount_options();
+            let mount_options = mount_options.iter().map(std::ffi::OsString::as_os_str).collect::<Vec<_>>();
 
-            let context = mangadex_fs::Context::new(polyfuse_server.try_clone()?, uid, gid);
+            let mut polyfuse_server = polyfuse_tokio::Server::mount(mountpoint, &mount_options).await?;
+
+            let context = mangadex_fs::Context::new(polyfuse_server.try_clone()?, config.clone(), uid, gid);
+
+            if config.auto_mark_read {
+                tokio::spawn(context.clone().run_mark_read_queue());
+            }
 
             let polyfuse_context = context.clone();
             let (polyfuse_sig_tx, polyfuse_sig) = tokio::sync::oneshot::channel();
@@ -70,6 +93,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 polyfuse_server.run_until(mangadex_fs::MangaDexFS::new(polyfuse_context), polyfuse_sig).await
             });
 
+            tokio::spawn(context.clone().load_pinned());
+
             let mut connection_counter = 0u64;
 
             loop {
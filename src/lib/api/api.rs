@@ -17,6 +17,11 @@ pub type GetPageError = reqwest::Error;
 pub type GetCoverError = reqwest::Error;
 pub type MDListError = reqwest::Error;
 
+// Whether the request failed for the network being unreachable, rather than for the server's answer.
+pub fn is_offline(error: &reqwest::Error) -> bool {
+    error.is_connect() || error.is_timeout()
+}
+
 impl MangaDexAPI {
     pub fn new() -> MangaDexAPI {
         MangaDexAPI {
@@ -64,6 +69,14 @@ impl MangaDexAPI {
         api::Chapter::get(&self.client, id).await
     }
 
+    pub async fn get_manga_raw(&self, id: u64) -> Result<Vec<u8>, GetMangaError> {
+        api::Manga::get_raw(&self.client, id).await
+    }
+
+    pub async fn get_chapter_raw(&self, id: u64) -> Result<Vec<u8>, GetChapterError> {
+        api::Chapter::get_raw(&self.client, id).await
+    }
+
     pub async fn get_page(&self, chapter_id: u64, url: &reqwest::Url) -> Result<api::Page, GetPageError> {
         api::Page::get(&self.client, chapter_id, url).await
     }
@@ -117,4 +130,14 @@ impl MangaDexAPI {
             None => Err(APIError::NotLoggedIn)
         }
     }
+
+    pub async fn user_id(&self) -> Result<u64, api::APIError> {
+        match &self.session {
+            Some(session) => match api::user_id(&self.client, session).await.map_err(APIError::Request)? {
+                Some(id) => Ok(id),
+                None => Err(APIError::NotLoggedIn)
+            },
+            None => Err(APIError::NotLoggedIn)
+        }
+    }
 }
\ No newline at end of file
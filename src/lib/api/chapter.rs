@@ -23,10 +23,28 @@ pub struct Chapter {
 }
 
 impl Chapter {
+    fn url(id: u64) -> reqwest::Url {
+        reqwest::Url::parse("https://mangadex.org/api/chapter/").unwrap().join(&id.to_string()).unwrap()
+    }
+
     pub async fn get(client: &reqwest::Client, id: u64) -> Result<Chapter, reqwest::Error> {
         client
-            .get(reqwest::Url::parse("https://mangadex.org/api/chapter/").unwrap().join(&id.to_string()).unwrap())
+            .get(Chapter::url(id))
             .send().await?
             .json().await
     }
+
+    // The response body as it is, for storing it.
+    pub async fn get_raw(client: &reqwest::Client, id: u64) -> Result<Vec<u8>, reqwest::Error> {
+        Ok(client
+            .get(Chapter::url(id))
+            .send().await?
+            .error_for_status()?
+            .bytes().await?
+            .to_vec())
+    }
+
+    pub fn from_raw(raw: &[u8]) -> Option<Chapter> {
+        serde_json::from_slice(raw).ok()
+    }
 }
\ No newline at end of file
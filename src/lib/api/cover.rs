@@ -4,7 +4,8 @@ impl Cover {
     pub async fn get(client: &reqwest::Client, url: &reqwest::Url) -> Result<Cover, reqwest::Error> {
         let response = client
             .get(url.as_ref())
-            .send().await?;
+            .send().await?
+            .error_for_status()?;
         
         Ok(Cover(response.bytes().await?.to_vec()))
     }
@@ -82,6 +82,92 @@ pub enum Genre {
     Mafia = 84
 }
 
+impl Genre {
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            Genre::FourKoma => "4-Koma",
+            Genre::Action => "Action",
+            Genre::Adventure => "Adventure",
+            Genre::AwardWinning => "Award Winning",
+            Genre::Comedy => "Comedy",
+            Genre::Cooking => "Cooking",
+            Genre::Doujinshi => "Doujinshi",
+            Genre::Drama => "Drama",
+            Genre::Ecchi => "Ecchi",
+            Genre::Fantasy => "Fantasy",
+            Genre::Gyaru => "Gyaru",
+            Genre::Harem => "Harem",
+            Genre::Historical => "Historical",
+            Genre::Horror => "Horror",
+            Genre::MartialArts => "Martial Arts",
+            Genre::Mecha => "Mecha",
+            Genre::Medical => "Medical",
+            Genre::Music => "Music",
+            Genre::Mystery => "Mystery",
+            Genre::Oneshot => "Oneshot",
+            Genre::Psychological => "Psychological",
+            Genre::Romance => "Romance",
+            Genre::SchoolLife => "School Life",
+            Genre::SciFi => "Sci-Fi",
+            Genre::ShoujoAi => "Shoujo Ai",
+            Genre::ShounenAi => "Shounen Ai",
+            Genre::SliceOfLife => "Slice of Life",
+            Genre::Smut => "Smut",
+            Genre::Sports => "Sports",
+            Genre::Supernatural => "Supernatural",
+            Genre::Tragedy => "Tragedy",
+            Genre::LongStrip => "Long Strip",
+            Genre::Yaoi => "Yaoi",
+            Genre::Yuri => "Yuri",
+            Genre::VideoGames => "Video Games",
+            Genre::Isekai => "Isekai",
+            Genre::Adaptation => "Adaptation",
+            Genre::Anthology => "Anthology",
+            Genre::WebComic => "Web Comic",
+            Genre::FullColor => "Full Color",
+            Genre::UserCreated => "User Created",
+            Genre::OfficialColored => "Official Colored",
+            Genre::FanColored => "Fan Colored",
+            Genre::Gore => "Gore",
+            Genre::SexualViolence => "Sexual Violence",
+            Genre::Crime => "Crime",
+            Genre::MagicalGirls => "Magical Girls",
+            Genre::Philosophical => "Philosophical",
+            Genre::Superhero => "Superhero",
+            Genre::Thriller => "Thriller",
+            Genre::Wuxia => "Wuxia",
+            Genre::Aliens => "Aliens",
+            Genre::Animals => "Animals",
+            Genre::Crossdressing => "Crossdressing",
+            Genre::Demons => "Demons",
+            Genre::Delinquents => "Delinquents",
+            Genre::Genderswap => "Genderswap",
+            Genre::Ghosts => "Ghosts",
+            Genre::MonsterGirls => "Monster Girls",
+            Genre::Loli => "Loli",
+            Genre::Magic => "Magic",
+            Genre::Military => "Military",
+            Genre::Monsters => "Monsters",
+            Genre::Ninja => "Ninja",
+            Genre::OfficeWorkers => "Office Workers",
+            Genre::Police => "Police",
+            Genre::PostApocalyptic => "Post-Apocalyptic",
+            Genre::Reincarnation => "Reincarnation",
+            Genre::ReverseHarem => "Reverse Harem",
+            Genre::Samurai => "Samurai",
+            Genre::Shota => "Shota",
+            Genre::Survival => "Survival",
+            Genre::TimeTravel => "Time Travel",
+            Genre::Vampires => "Vampires",
+            Genre::TraditionalGames => "Traditional Games",
+            Genre::VirtualReality => "Virtual Reality",
+            Genre::Zombies => "Zombies",
+            Genre::Incest => "Incest",
+            Genre::Mafia => "Mafia"
+        }
+    }
+}
+
 impl std::convert::TryFrom<u8> for Genre {
     type Error = ();
     
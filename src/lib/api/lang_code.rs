@@ -0,0 +1,29 @@
+pub fn iso_639_1(lang_code: &str) -> &str {
+    match lang_code {
+        "gb" => "en",
+        "jp" => "ja",
+        "br" | "pt" => "pt",
+        "mx" | "es" => "es",
+        "cn" | "hk" => "zh",
+        "kr" => "ko",
+        "vn" => "vi",
+        "sa" => "ar",
+        "ir" => "fa",
+        "il" => "he",
+        "in" => "hi",
+        "bd" => "bn",
+        "my" => "ms",
+        "ph" => "fil",
+        "se" => "sv",
+        "dk" => "da",
+        "gr" => "el",
+        "cz" => "cs",
+        "ua" => "uk",
+        "rs" => "sr",
+        "ct" => "ca",
+        "mm" => "my",
+        "mn" => "mn",
+        "np" => "ne",
+        other => other
+    }
+}
\ No newline at end of file
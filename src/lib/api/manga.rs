@@ -48,6 +48,18 @@ pub struct ChapterField {
     pub title: String,
     pub lang_code: String,
     pub timestamp: u32,
+    #[serde(default)]
+    pub group_id: u64,
+    #[serde(default)]
+    pub group_name: Option<String>,
+    #[serde(default)]
+    pub group_id_2: u64,
+    #[serde(default)]
+    pub group_name_2: Option<String>,
+    #[serde(default)]
+    pub group_id_3: u64,
+    #[serde(default)]
+    pub group_name_3: Option<String>,
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -57,10 +69,28 @@ pub struct Manga {
 }
 
 impl Manga {
+    fn url(id: u64) -> reqwest::Url {
+        reqwest::Url::parse("https://mangadex.org/api/manga/").unwrap().join(&id.to_string()).unwrap()
+    }
+
     pub async fn get(client: &reqwest::Client, id: u64) -> Result<Manga, reqwest::Error> {
         client
-            .get(reqwest::Url::parse("https://mangadex.org/api/manga/").unwrap().join(&id.to_string()).unwrap())
+            .get(Manga::url(id))
             .send().await?
             .json::<Manga>().await
     }
+
+    // The response body as it is, for storing it.
+    pub async fn get_raw(client: &reqwest::Client, id: u64) -> Result<Vec<u8>, reqwest::Error> {
+        Ok(client
+            .get(Manga::url(id))
+            .send().await?
+            .error_for_status()?
+            .bytes().await?
+            .to_vec())
+    }
+
+    pub fn from_raw(raw: &[u8]) -> Option<Manga> {
+        serde_json::from_slice(raw).ok()
+    }
 }
\ No newline at end of file
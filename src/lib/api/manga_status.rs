@@ -7,6 +7,17 @@ pub enum MangaStatus {
     Hiatus = 4
 }
 
+impl MangaStatus {
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            MangaStatus::OnGoing => "Ongoing",
+            MangaStatus::Completed => "Completed",
+            MangaStatus::Cancelled => "Cancelled",
+            MangaStatus::Hiatus => "Hiatus"
+        }
+    }
+}
+
 impl std::convert::TryFrom<u8> for MangaStatus {
     type Error = ();
     
@@ -13,6 +13,8 @@ pub mod unfollow;
 pub mod mark_chapter;
 pub mod follows;
 pub mod cover;
+pub mod lang_code;
+pub mod user;
 
 pub use session::*;
 pub use manga::*;
@@ -28,4 +30,6 @@ pub use follow::*;
 pub use unfollow::*;
 pub use mark_chapter::*;
 pub use follows::*;
-pub use cover::*;
\ No newline at end of file
+pub use cover::*;
+pub use lang_code::*;
+pub use user::*;
\ No newline at end of file
@@ -1,14 +1,44 @@
+enum Body {
+    Response(reqwest::Response),
+    Stored(Vec<u8>)
+}
+
 pub struct Page {
     pub chapter_id: u64,
-    pub data: Vec<u8>
+    pub length: Option<u64>,
+    body: Body
 }
 
 impl Page {
+    // Returns as soon as the headers are in, the body is read with `stream`.
     pub async fn get(client: &reqwest::Client, chapter_id: u64, url: &reqwest::Url) -> Result<Page, reqwest::Error> {
         let response = client
             .get(url.as_ref())
-            .send().await?;
-        
-        Ok(Page { chapter_id: chapter_id, data: response.bytes().await?.to_vec() })
+            .send().await?
+            .error_for_status()?;
+
+        Ok(Page { chapter_id: chapter_id, length: response.content_length(), body: Body::Response(response) })
+    }
+
+    // A page read back from the disk instead of downloaded.
+    pub fn stored(chapter_id: u64, data: Vec<u8>) -> Page {
+        Page { chapter_id, length: Some(data.len() as u64), body: Body::Stored(data) }
+    }
+
+    // Hands the body to `push` chunk by chunk as it arrives.
+    pub async fn stream<F: FnMut(&[u8])>(self, mut push: F) -> Result<(), reqwest::Error> {
+        match self.body {
+            Body::Response(response) => {
+                let stream = response.bytes_stream();
+                tokio::pin!(stream);
+
+                while let Some(chunk) = tokio::stream::StreamExt::next(&mut stream).await {
+                    push(&chunk?);
+                }
+            },
+            Body::Stored(data) => push(&data)
+        }
+
+        Ok(())
     }
 }
\ No newline at end of file
@@ -222,6 +222,87 @@ impl Default for SearchParams {
     }
 }
 
+// Parses a whitespace separated "key=value" query, e.g. "title=vinland saga author=yukimura include=action,drama".
+// Words without a key are appended to the value of the preceding key, or to the title.
+impl std::convert::TryFrom<&str> for SearchParams {
+    type Error = ();
+
+    fn try_from(query: &str) -> Result<SearchParams, Self::Error> {
+        let mut pairs: Vec<(String, String)> = vec![];
+
+        for token in query.split_whitespace() {
+            match token.find('=') {
+                Some(index) => pairs.push((token[..index].to_lowercase(), token[index + 1..].to_owned())),
+                None => match pairs.last_mut() {
+                    Some((_, value)) => {
+                        value.push(' ');
+                        value.push_str(token);
+                    },
+                    None => pairs.push((String::from("title"), token.to_owned()))
+                }
+            }
+        }
+
+        let mut params = SearchParams::default();
+
+        for (key, value) in pairs {
+            match key.as_str() {
+                "title" => params.title = value,
+                "author" => params.author = Some(value),
+                "artist" => params.artist = Some(value),
+                "lang" | "language" => params.original_language = Some(Language::try_from(value.to_lowercase().as_str())?),
+                "demo" | "demographic" => {
+                    params.demographic = Demographic { shounen: false, shoujo: false, seinen: false, josei: false };
+
+                    for demographic in value.split(',') {
+                        match demographic {
+                            "shounen" => params.demographic.shounen = true,
+                            "shoujo" => params.demographic.shoujo = true,
+                            "seinen" => params.demographic.seinen = true,
+                            "josei" => params.demographic.josei = true,
+                            _ => return Err(())
+                        }
+                    }
+                },
+                "status" | "publication" => {
+                    params.publication = PublicationStatus { ongoing: false, completed: false, cancelled: false, hiatus: false };
+
+                    for status in value.split(',') {
+                        match status {
+                            "ongoing" => params.publication.ongoing = true,
+                            "completed" => params.publication.completed = true,
+                            "cancelled" => params.publication.cancelled = true,
+                            "hiatus" => params.publication.hiatus = true,
+                            _ => return Err(())
+                        }
+                    }
+                },
+                "include" | "tags" => for tag in value.split(',') {
+                    params.include_tag.push(api::Genre::try_from(tag)?);
+                },
+                "exclude" => for tag in value.split(',') {
+                    params.exclude_tag.push(api::Genre::try_from(tag)?);
+                },
+                "inclusion" => params.inclusion_mode = match value.as_str() {
+                    "all" => TagMode::All,
+                    "any" => TagMode::Any,
+                    _ => return Err(())
+                },
+                "exclusion" => params.exclusion_mode = match value.as_str() {
+                    "all" => TagMode::All,
+                    "any" => TagMode::Any,
+                    _ => return Err(())
+                },
+                "sort" => params.sort_by = SortBy(SortMode::Ascending, SortParameter::try_from(value.as_str())?),
+                "sort-descending" => params.sort_by = SortBy(SortMode::Descending, SortParameter::try_from(value.as_str())?),
+                _ => return Err(())
+            }
+        }
+
+        Ok(params)
+    }
+}
+
 #[derive(Debug)]
 pub struct SearchEntry {
     pub id: u64,
@@ -377,4 +458,63 @@ pub async fn search(client: &reqwest::Client, session: &api::MangaDexSession, pa
             SearchEntry { id, title: title.to_string(), author: author.to_string(), status, last_update }
         })
         .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn search_params_keys() {
+        let params = SearchParams::try_from("title=vinland saga author=yukimura lang=jp include=action,drama sort-descending=rating").unwrap();
+
+        assert_eq!(params.title, "vinland saga");
+        assert_eq!(params.author.as_deref(), Some("yukimura"));
+        assert_eq!(params.artist, None);
+        assert!(matches!(params.original_language, Some(Language::Japanese)));
+        assert_eq!(params.include_tag, vec![api::Genre::Action, api::Genre::Drama]);
+        assert!(matches!(params.sort_by, SortBy(SortMode::Descending, SortParameter::Rating)));
+    }
+
+    #[test]
+    fn search_params_words_without_key() {
+        let params = SearchParams::try_from("vinland saga").unwrap();
+        assert_eq!(params.title, "vinland saga");
+
+        let params = SearchParams::try_from("Author=makoto yukimura").unwrap();
+        assert_eq!(params.title, "");
+        assert_eq!(params.author.as_deref(), Some("makoto yukimura"));
+    }
+
+    #[test]
+    fn search_params_lists_replace_defaults() {
+        let params = SearchParams::try_from("demographic=seinen,josei status=completed").unwrap();
+
+        assert!(!params.demographic.shounen && !params.demographic.shoujo);
+        assert!(params.demographic.seinen && params.demographic.josei);
+        assert!(params.publication.completed);
+        assert!(!params.publication.ongoing && !params.publication.cancelled && !params.publication.hiatus);
+    }
+
+    #[test]
+    fn search_params_defaults() {
+        let params = SearchParams::try_from("").unwrap();
+
+        assert_eq!(params.title, "");
+        assert!(params.include_tag.is_empty() && params.exclude_tag.is_empty());
+        assert!(matches!(params.inclusion_mode, TagMode::All));
+        assert!(matches!(params.exclusion_mode, TagMode::Any));
+        assert!(matches!(params.sort_by, SortBy(SortMode::Ascending, SortParameter::LastUpdated)));
+    }
+
+    #[test]
+    fn invalid_search_params() {
+        assert!(SearchParams::try_from("colour=blue").is_err());
+        assert!(SearchParams::try_from("lang=klingon").is_err());
+        assert!(SearchParams::try_from("demographic=kodomo").is_err());
+        assert!(SearchParams::try_from("include=action,notagenre").is_err());
+        assert!(SearchParams::try_from("inclusion=some").is_err());
+        assert!(SearchParams::try_from("sort=random").is_err());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,37 @@
+use crate::api;
+
+fn headers(session: &api::MangaDexSession) -> reqwest::header::HeaderMap {
+    let mut headers = reqwest::header::HeaderMap::new();
+
+    headers.append(
+        reqwest::header::USER_AGENT,
+        api::user_agent()
+    );
+
+    headers.append(
+        reqwest::header::COOKIE,
+        reqwest::header::HeaderValue::from_str(&format!("mangadex_session={}", session.id))
+            .unwrap()
+    );
+
+    headers
+}
+
+// The logged-in user's profile link ("/user/<id>/<name>") is part of the navbar on every page.
+pub async fn user_id(client: &reqwest::Client, session: &api::MangaDexSession) -> Result<Option<u64>, reqwest::Error> {    
+    let url = reqwest::Url::parse("https://mangadex.org/").unwrap();
+
+    let text = client
+        .get(url)
+        .headers(headers(session))
+        .send().await?
+        .text().await?;
+        
+    let html = scraper::Html::parse_document(text.as_str());
+
+    Ok(html.select(&scraper::Selector::parse("a[href^=\"/user/\"]").unwrap())
+        .filter_map(|link| link.value().attr("href"))
+        .filter_map(|href| href.split('/').nth(2))
+        .filter_map(|id| id.parse::<u64>().ok())
+        .next())
+}
\ No newline at end of file
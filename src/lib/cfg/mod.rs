@@ -19,23 +19,182 @@ pub fn default_socket_path() -> std::path::PathBuf {
     runtime_dir.join(std::path::Path::new(DEFAULT_SOCKET_NAME))
 }
 
-#[derive(serde::Deserialize)]
+pub fn default_store_path() -> std::path::PathBuf {
+    project_dirs().data_dir().join(std::path::Path::new("pinned"))
+}
+
+pub fn default_languages() -> Vec<String> {
+    vec![String::from("gb")]
+}
+
+pub fn default_refresh_interval() -> u64 {
+    300u64
+}
+
+pub fn default_auto_mark_read_fraction() -> f64 {
+    1f64
+}
+
+pub fn default_max_downloads() -> usize {
+    4usize
+}
+
+pub fn default_strip_height() -> u32 {
+    5000u32
+}
+
+pub fn default_eink_width() -> u32 {
+    1072u32
+}
+
+pub fn default_eink_height() -> u32 {
+    1448u32
+}
+
+pub fn default_fsname() -> String {
+    String::from("mangadex-fs")
+}
+
+// Permissions are given in octal, "444" or "0o444". A TOML integer would be read as decimal, so only strings are taken.
+pub fn parse_mode(mode: &str) -> Result<u32, String> {
+    let digits = mode.trim().trim_start_matches("0o");
+
+    match u32::from_str_radix(digits, 8) {
+        Ok(mode) if mode <= 0o7777 && !digits.starts_with('+') => Ok(mode),
+        Ok(_) => Err(format!("invalid mode \"{}\", must be at most 7777", mode)),
+        Err(_) => Err(format!("invalid mode \"{}\", expected octal permissions such as \"444\"", mode))
+    }
+}
+
+fn deserialize_mode<'de, D>(deserializer: D) -> Result<Option<u32>, D::Error>
+where
+    D: serde::Deserializer<'de>
+{
+    let mode = <String as serde::Deserialize>::deserialize(deserializer)?;
+
+    parse_mode(&mode).map(Some).map_err(serde::de::Error::custom)
+}
+
+#[derive(Clone, serde::Deserialize)]
 pub struct Config {
     #[serde(default = "default_socket_path")]
     pub socket: std::path::PathBuf,
-    pub mountpoint: Option<std::path::PathBuf>
+    pub mountpoint: Option<std::path::PathBuf>,
+    #[serde(default = "default_languages")]
+    pub languages: Vec<String>,
+    #[serde(default)]
+    pub language_directories: bool,
+    #[serde(default)]
+    pub preferred_groups: Vec<String>,
+    #[serde(default)]
+    pub blocked_groups: Vec<String>,
+    #[serde(default)]
+    pub collapse_chapters: bool,
+    #[serde(default)]
+    pub show_group_names: bool,
+    pub manga_template: Option<String>,
+    pub chapter_template: Option<String>,
+    #[serde(default)]
+    pub stitch_long_strips: bool,
+    #[serde(default = "default_strip_height")]
+    pub strip_height: u32,
+    #[serde(default)]
+    pub split_spreads: bool,
+    #[serde(default)]
+    pub exports: Vec<String>,
+    #[serde(default)]
+    pub volume_exports: bool,
+    #[serde(default)]
+    pub eink_view: bool,
+    #[serde(default)]
+    pub eink_pages: bool,
+    #[serde(default = "default_eink_width")]
+    pub eink_width: u32,
+    #[serde(default = "default_eink_height")]
+    pub eink_height: u32,
+    #[serde(default)]
+    pub eink_contrast: f32,
+    #[serde(default)]
+    pub allow_other: bool,
+    #[serde(default)]
+    pub default_permissions: bool,
+    #[serde(default = "default_fsname")]
+    pub fsname: String,
+    #[serde(default = "default_fsname")]
+    pub subtype: String,
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+    #[serde(default, deserialize_with = "deserialize_mode")]
+    pub mode: Option<u32>,
+    #[serde(default = "default_refresh_interval")]
+    pub refresh_interval: u64,
+    #[serde(default)]
+    pub auto_mark_read: bool,
+    #[serde(default = "default_auto_mark_read_fraction")]
+    pub auto_mark_read_fraction: f64,
+    pub read_ahead: Option<f64>,
+    #[serde(default = "default_max_downloads")]
+    pub max_downloads: usize,
+    #[serde(default = "default_store_path")]
+    pub store: std::path::PathBuf
 }
 
 impl std::default::Default for Config {
     fn default() -> Config {
         Config {
             socket: default_socket_path(),
-            mountpoint: None
+            mountpoint: None,
+            languages: default_languages(),
+            language_directories: false,
+            preferred_groups: vec![],
+            blocked_groups: vec![],
+            collapse_chapters: false,
+            show_group_names: false,
+            manga_template: None,
+            chapter_template: None,
+            stitch_long_strips: false,
+            strip_height: default_strip_height(),
+            split_spreads: false,
+            exports: vec![],
+            volume_exports: false,
+            eink_view: false,
+            eink_pages: false,
+            eink_width: default_eink_width(),
+            eink_height: default_eink_height(),
+            eink_contrast: 0f32,
+            allow_other: false,
+            default_permissions: false,
+            fsname: default_fsname(),
+            subtype: default_fsname(),
+            uid: None,
+            gid: None,
+            mode: None,
+            refresh_interval: default_refresh_interval(),
+            auto_mark_read: false,
+            auto_mark_read_fraction: default_auto_mark_read_fraction(),
+            read_ahead: None,
+            max_downloads: default_max_downloads(),
+            store: default_store_path()
         }
     }
 }
 
 impl Config {
+    // Arguments for fusermount.
+    pub fn mount_options(&self) -> Vec<std::ffi::OsString> {
+        let mut options = vec![format!("fsname={}", self.fsname), format!("subtype={}", self.subtype)];
+
+        if self.allow_other {
+            options.push(String::from("allow_other"));
+        }
+
+        if self.default_permissions {
+            options.push(String::from("default_permissions"));
+        }
+
+        vec!["-o".into(), options.join(",").into()]
+    }
+
     pub async fn load() -> std::io::Result<Result<Config, toml::de::Error>> {
         let config_path = config_file_path();
         let mut file = tokio::fs::File::open(config_path).await?;
@@ -45,4 +204,36 @@ impl Config {
 
         Ok(toml::from_slice(&contents))
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modes() {
+        assert_eq!(parse_mode("444"), Ok(0o444));
+        assert_eq!(parse_mode("0o755"), Ok(0o755));
+        assert_eq!(parse_mode("0755"), Ok(0o755));
+        assert_eq!(parse_mode("7777"), Ok(0o7777));
+    }
+
+    #[test]
+    fn invalid_modes() {
+        assert!(parse_mode("").is_err());
+        assert!(parse_mode("888").is_err());
+        assert!(parse_mode("17777").is_err());
+        assert!(parse_mode("+444").is_err());
+        assert!(parse_mode("rwx").is_err());
+    }
+
+    #[test]
+    fn mode_from_toml() {
+        let config = |mode: &str| toml::from_str::<Config>(&format!("socket = \"mangadex-fsd.sock\"\n{}", mode)).map(|config| config.mode);
+
+        assert_eq!(config("mode = \"755\""), Ok(Some(0o755)));
+        assert_eq!(config(""), Ok(None));
+        assert!(config("mode = 755").is_err());
+        assert!(config("mode = \"9\"").is_err());
+    }
+}
@@ -1,11 +1,50 @@
 use crate::api;
+use crate::cfg;
 use crate::fs;
 
+// Inodes of the directories made at startup. Everything made later is numbered from `FIRST_DYNAMIC_INO` on.
+pub(crate) const ROOT_INO: u64 = 1u64;
+pub(crate) const BY_ID_INO: u64 = 2u64;
+pub(crate) const BY_ID_MANGA_INO: u64 = 3u64;
+pub(crate) const BY_ID_CHAPTER_INO: u64 = 4u64;
+pub(crate) const SEARCH_INO: u64 = 5u64;
+pub(crate) const FOLLOWS_INO: u64 = 6u64;
+pub(crate) const MDLIST_INO: u64 = 7u64;
+// One per MD list status, in the order of `MDLIST_STATUSES`.
+pub(crate) const FIRST_MDLIST_STATUS_INO: u64 = 8u64;
+pub(crate) const BY_GENRE_INO: u64 = 14u64;
+pub(crate) const BY_AUTHOR_INO: u64 = 15u64;
+pub(crate) const BY_STATUS_INO: u64 = 16u64;
+const FIRST_DYNAMIC_INO: u64 = 17u64;
+
+const INDEX_INODES: [u64; 3] = [BY_GENRE_INO, BY_AUTHOR_INO, BY_STATUS_INO];
+
+const MDLIST_STATUSES: [(&str, api::MDListStatus); 6] = [
+    ("reading", api::MDListStatus::Reading),
+    ("completed", api::MDListStatus::Completed),
+    ("on-hold", api::MDListStatus::OnHold),
+    ("plan-to-read", api::MDListStatus::PlanToRead),
+    ("dropped", api::MDListStatus::Dropped),
+    ("re-reading", api::MDListStatus::ReReading)
+];
+
+const MARK_READ_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10u64);
+
+// The chapter fetched in the background while `from` is being read.
+struct ReadAhead {
+    from: u64,
+    to: u64
+}
+
 pub struct Context {
     pub manga: tokio::sync::RwLock<std::collections::HashMap<u64, std::sync::Arc<fs::entry::Manga>>>,
     pub chapters: tokio::sync::RwLock<std::collections::HashMap<u64, std::sync::Arc<fs::entry::Chapter>>>,
     pub pages: tokio::sync::RwLock<std::collections::HashMap<reqwest::Url, std::sync::Arc<fs::entry::Page>>>,
     pub covers: tokio::sync::RwLock<std::collections::HashMap<reqwest::Url, std::sync::Arc<fs::entry::Cover>>>,
+    pub strips: tokio::sync::RwLock<std::collections::HashMap<u64, std::sync::Arc<Vec<std::sync::Arc<fs::entry::StripImage>>>>>,
+    pub transcoded: tokio::sync::RwLock<std::collections::HashMap<reqwest::Url, std::sync::Arc<fs::entry::Transcoded>>>,
+    pub spreads: tokio::sync::RwLock<std::collections::HashMap<u64, std::sync::Arc<Vec<std::sync::Arc<fs::entry::SpreadHalf>>>>>,
+    pub exports: tokio::sync::RwLock<std::collections::HashMap<fs::entry::ExportKey, std::sync::Arc<fs::entry::Export>>>,
 
     pub entries: tokio::sync::RwLock<std::collections::HashMap<u64, fs::entry::Inode>>,
     manga_inodes: tokio::sync::RwLock<std::collections::HashMap<u64, u64>>,
@@ -13,8 +52,26 @@ pub struct Context {
     pages_inodes: tokio::sync::RwLock<std::collections::HashMap<reqwest::Url, u64>>,
     cover_inodes: tokio::sync::RwLock<std::collections::HashMap<reqwest::Url, u64>>,
 
+    manga_in_flight: crate::in_flight::InFlight<u64>,
+    chapters_in_flight: crate::in_flight::InFlight<u64>,
+    pages_in_flight: crate::in_flight::InFlight<reqwest::Url>,
+    covers_in_flight: crate::in_flight::InFlight<reqwest::Url>,
+    strips_in_flight: crate::in_flight::InFlight<u64>,
+    transcoded_in_flight: crate::in_flight::InFlight<reqwest::Url>,
+    spreads_in_flight: crate::in_flight::InFlight<u64>,
+    exports_in_flight: crate::in_flight::InFlight<fs::entry::ExportKey>,
+
+    pub config: cfg::Config,
     server: tokio::sync::Mutex<polyfuse_tokio::Server>,
     api: tokio::sync::RwLock<api::MangaDexAPI>,
+    user_id: tokio::sync::RwLock<Option<u64>>,
+    pages_read: tokio::sync::Mutex<std::collections::HashMap<u64, std::collections::HashSet<usize>>>,
+    mark_read_queue: tokio::sync::Mutex<std::collections::HashSet<u64>>,
+    marked_read: tokio::sync::Mutex<std::collections::HashSet<u64>>,
+    read_ahead: tokio::sync::Mutex<Option<ReadAhead>>,
+    pinning: tokio::sync::Mutex<std::collections::HashSet<u64>>,
+    store: crate::store::Store,
+    downloads: std::sync::Arc<crate::scheduler::Scheduler>,
     next_ino: tokio::sync::Mutex<u64>,
     uid: nix::unistd::Uid,
     gid: nix::unistd::Gid
@@ -36,26 +93,106 @@ impl<T> GetOrFetch<T> {
 
 pub type GetOrFetchRef<T> = GetOrFetch<std::sync::Weak<T>>;
 
+// When MangaDex can't be reached, falls back on what is stored for pinned manga.
+async fn or_pinned<T, F>(result: Result<T, reqwest::Error>, what: String, stored: F) -> Result<T, reqwest::Error>
+where
+    F: std::future::Future<Output = Option<T>>
+{
+    match result {
+        Err(error) if api::is_offline(&error) => match stored.await {
+            Some(value) => {
+                debug!("MangaDex is unreachable, reading pinned {}", what);
+                Ok(value)
+            },
+            None => {
+                warn!("MangaDex is unreachable and {} is not pinned: {}", what, error);
+                Err(error)
+            }
+        },
+        result => result
+    }
+}
+
+// Keeps the download slot of a page until the page has finished streaming.
+fn hold_until_complete(ticket: crate::scheduler::Ticket, page_ref: std::sync::Weak<fs::entry::Page>) {
+    if let Some(page) = page_ref.upgrade() {
+        tokio::spawn(async move {
+            page.data.complete().await;
+            drop(ticket);
+        });
+    }
+}
+
 impl Context {
-    pub fn new(server: polyfuse_tokio::Server, uid: nix::unistd::Uid, gid: nix::unistd::Gid) -> std::sync::Arc<Context> {
+    pub fn new(server: polyfuse_tokio::Server, config: cfg::Config, uid: nix::unistd::Uid, gid: nix::unistd::Gid) -> std::sync::Arc<Context> {
         let mut entries = std::collections::HashMap::new();
+        let mut root = fs::entry::Directory::root();
+
+        let mut by_id = fs::entry::Directory::new(ROOT_INO);
+        by_id.children.insert("manga".into(), (BY_ID_MANGA_INO, false));
+        by_id.children.insert("chapter".into(), (BY_ID_CHAPTER_INO, false));
+        root.children.insert("by-id".into(), (BY_ID_INO, false));
+        root.children.insert("search".into(), (SEARCH_INO, false));
+        root.children.insert("follows".into(), (FOLLOWS_INO, false));
+        root.children.insert("mdlist".into(), (MDLIST_INO, false));
+        root.children.insert("by-genre".into(), (BY_GENRE_INO, false));
+        root.children.insert("by-author".into(), (BY_AUTHOR_INO, false));
+        root.children.insert("by-status".into(), (BY_STATUS_INO, false));
+
+        let mut mdlist = fs::entry::Directory::new(ROOT_INO);
+        for (index, (name, status)) in MDLIST_STATUSES.iter().enumerate() {
+            let status_ino = FIRST_MDLIST_STATUS_INO + index as u64;
+            mdlist.children.insert((*name).into(), (status_ino, false));
+            entries.insert(status_ino, fs::entry::Inode(fs::entry::Entry::MDList(*status, fs::entry::Directory::new(MDLIST_INO), None), fs::entry::Attributes::new(status_ino, uid, gid, config.mode)));
+        }
+
+        entries.insert(ROOT_INO, fs::entry::Inode(fs::entry::Entry::Root(root), fs::entry::Attributes::new(ROOT_INO, uid, gid, config.mode)));
+        entries.insert(BY_ID_INO, fs::entry::Inode(fs::entry::Entry::Directory(by_id), fs::entry::Attributes::new(BY_ID_INO, uid, gid, config.mode)));
+        entries.insert(BY_ID_MANGA_INO, fs::entry::Inode(fs::entry::Entry::ByIdManga, fs::entry::Attributes::new(BY_ID_MANGA_INO, uid, gid, config.mode)));
+        entries.insert(BY_ID_CHAPTER_INO, fs::entry::Inode(fs::entry::Entry::ByIdChapter, fs::entry::Attributes::new(BY_ID_CHAPTER_INO, uid, gid, config.mode)));
+        entries.insert(SEARCH_INO, fs::entry::Inode(fs::entry::Entry::Search(fs::entry::Directory::new(ROOT_INO)), fs::entry::Attributes::new(SEARCH_INO, uid, gid, config.mode)));
+        entries.insert(FOLLOWS_INO, fs::entry::Inode(fs::entry::Entry::Follows(fs::entry::Directory::new(ROOT_INO), None), fs::entry::Attributes::new(FOLLOWS_INO, uid, gid, config.mode)));
+        entries.insert(MDLIST_INO, fs::entry::Inode(fs::entry::Entry::Directory(mdlist), fs::entry::Attributes::new(MDLIST_INO, uid, gid, config.mode)));
 
-        entries.insert(1u64, fs::entry::Inode(fs::entry::Entry::Root(fs::entry::Directory::root()), fs::entry::Attributes::new(1u64, uid.clone(), gid.clone())));
+        for index_ino in INDEX_INODES.iter() {
+            entries.insert(*index_ino, fs::entry::Inode(fs::entry::Entry::Directory(fs::entry::Directory::new(ROOT_INO)), fs::entry::Attributes::new(*index_ino, uid, gid, config.mode)));
+        }
 
         std::sync::Arc::new(Context {
+            downloads: std::sync::Arc::new(crate::scheduler::Scheduler::new(config.max_downloads)),
+            store: crate::store::Store::open(config.store.clone()),
+            config,
             server: tokio::sync::Mutex::new(server),
             api: tokio::sync::RwLock::new(api::MangaDexAPI::new()),
+            user_id: tokio::sync::RwLock::new(None),
+            pages_read: tokio::sync::Mutex::default(),
+            mark_read_queue: tokio::sync::Mutex::default(),
+            marked_read: tokio::sync::Mutex::default(),
+            read_ahead: tokio::sync::Mutex::new(None),
+            pinning: tokio::sync::Mutex::default(),
             manga: tokio::sync::RwLock::new(std::collections::HashMap::default()),
             chapters: tokio::sync::RwLock::new(std::collections::HashMap::default()),
             pages: tokio::sync::RwLock::new(std::collections::HashMap::default()),
             covers: tokio::sync::RwLock::new(std::collections::HashMap::default()),
+            strips: tokio::sync::RwLock::new(std::collections::HashMap::default()),
+            transcoded: tokio::sync::RwLock::new(std::collections::HashMap::default()),
+            spreads: tokio::sync::RwLock::new(std::collections::HashMap::default()),
+            exports: tokio::sync::RwLock::new(std::collections::HashMap::default()),
             entries: tokio::sync::RwLock::new(entries),
-            next_ino: tokio::sync::Mutex::new(2u64),
+            next_ino: tokio::sync::Mutex::new(FIRST_DYNAMIC_INO),
             uid, gid,
             manga_inodes: tokio::sync::RwLock::default(),
             chapters_inodes: tokio::sync::RwLock::default(),
             pages_inodes: tokio::sync::RwLock::default(),
-            cover_inodes: tokio::sync::RwLock::default()
+            cover_inodes: tokio::sync::RwLock::default(),
+            manga_in_flight: crate::in_flight::InFlight::default(),
+            chapters_in_flight: crate::in_flight::InFlight::default(),
+            pages_in_flight: crate::in_flight::InFlight::default(),
+            covers_in_flight: crate::in_flight::InFlight::default(),
+            strips_in_flight: crate::in_flight::InFlight::default(),
+            transcoded_in_flight: crate::in_flight::InFlight::default(),
+            spreads_in_flight: crate::in_flight::InFlight::default(),
+            exports_in_flight: crate::in_flight::InFlight::default()
         })
     }
 
@@ -67,40 +204,82 @@ impl Context {
         ret
     }
 
+    // Replacing an existing inode (e.g. a fetched chapter taking over its not fetched inode) keeps its attributes.
     async fn new_node(&self, ino: u64, entry: fs::entry::Entry) {
         debug!("writing entry \"{}\" at ino: {}", entry.variant(), ino);
-        self.entries.write().await.insert(ino, fs::entry::Inode(entry, fs::entry::Attributes::new(ino, self.uid.clone(), self.gid.clone())));
+        let mut entries = self.entries.write().await;
+
+        let attributes = match entries.remove(&ino) {
+            Some(fs::entry::Inode(_, attributes)) => attributes,
+            None => fs::entry::Attributes::new(ino, self.uid.clone(), self.gid.clone(), self.config.mode)
+        };
+
+        entries.insert(ino, fs::entry::Inode(entry, attributes));
+    }
+
+    async fn set_time(&self, inodes: &[u64], timestamp: u64) {
+        if let Some(time) = fs::entry::timestamp_time(timestamp) {
+            let mut entries = self.entries.write().await;
+
+            for ino in inodes {
+                if let Some(fs::entry::Inode(_, attributes)) = entries.get_mut(ino) {
+                    attributes.set_time(time);
+                }
+            }
+        }
     }
 
     pub async fn log_in<L, P>(&self, login: L, password: P) -> Result<api::MangaDexSession, api::LogInError>
         where
         L: Into<std::borrow::Cow<'static, str>>,
         P: Into<std::borrow::Cow<'static, str>> {
+        self.user_id.write().await.take();
         self.api.write().await.log_in(login, password).await.map(std::clone::Clone::clone)
     }
 
     pub async fn log_out(&self) -> Result<(), api::LogOutError> {
+        self.user_id.write().await.take();
         self.api.write().await.log_out().await
     }
 
     pub async fn get_or_fetch_manga(&self, id: u64, languages: Vec<String>) -> Result<GetOrFetchRef<fs::entry::Manga>, api::GetMangaError> {
-        match self.manga.write().await.entry(id) {
-            std::collections::hash_map::Entry::Occupied(occupied) => Ok(GetOrFetchRef::Cached(std::sync::Arc::downgrade(occupied.get()))),
-            std::collections::hash_map::Entry::Vacant(vacant) => match self.api.read().await.get_manga(id).await {
+        self.get_or_make_manga(id, languages, None).await
+    }
+
+    // Like `get_or_fetch_manga`, with the API response already at hand if `manga_api` is given.
+    async fn get_or_make_manga(&self, id: u64, languages: Vec<String>, manga_api: Option<api::Manga>) -> Result<GetOrFetchRef<fs::entry::Manga>, api::GetMangaError> {
+        let result = self.fetch_manga(id, languages, manga_api).await;
+
+        if let Ok(GetOrFetch::Fetched(manga_ref)) = &result {
+            if let (Some(manga), Some(manga_ino)) = (manga_ref.upgrade(), self.manga_ino(id).await) {
+                let manga_name = match self.entries.read().await.get(&1u64) {
+                    Some(fs::entry::Inode(fs::entry::Entry::Root(directory), _)) => directory.child_name(manga_ino).cloned(),
+                    _ => None
+                };
+
+                if let Some(manga_name) = manga_name {
+                    self.index_manga(&manga, &manga_name).await;
+                }
+            }
+        }
+
+        result
+    }
+
+    async fn fetch_manga(&self, id: u64, languages: Vec<String>, manga_api: Option<api::Manga>) -> Result<GetOrFetchRef<fs::entry::Manga>, api::GetMangaError> {
+        self.manga_in_flight.get_or_fetch(&self.manga, id, || async move {
+            let result = match manga_api {
+                Some(manga_api) => Ok(manga_api),
+                None => or_pinned(self.api.read().await.get_manga(id).await, format!("manga {}", id), self.store.load_manga(id)).await
+            };
+
+            match result {
                 Ok(manga_api) => {
                     let manga = std::sync::Arc::new(fs::entry::Manga::new(id, manga_api));
 
                     let manga_ino = self.make_next_ino().await;
                     
-                    let mut directory = fs::entry::Directory::new(1u64);
-                    for chapter in &manga.chapters {
-                        if languages.iter().find(|&lang| lang == &chapter.lang_code).is_some() {
-                            let chapter_ino: u64 = self.make_next_ino().await;
-                            directory.children.insert(chapter.to_string().into(), (chapter_ino, false));
-                            self.new_node(chapter_ino, fs::entry::Entry::ChapterNotFetched(chapter.id)).await;
-                            self.chapters_inodes.write().await.insert(chapter.id, chapter_ino);
-                        }
-                    }
+                    let mut directory = self.make_manga_directory(manga_ino, &manga, &languages).await;
 
                     self.manga_inodes.write().await.insert(manga.id, manga_ino);
 
@@ -119,33 +298,435 @@ impl Context {
                         self.cover_inodes.write().await.insert(url.clone(), cover_ino);
 
                         debug!("fetching cover from {}", url);
+                        let _ticket = self.downloads.acquire(crate::scheduler::Priority::Readdir, None, url).await;
                         self.get_or_fetch_cover(id, &url).await.ok();
                     }
 
+                    // The manga directory and its cover are as old as the newest chapter.
+                    let mut timed_inodes = vec![manga_ino];
+                    if let Some(url) = &manga.cover {
+                        timed_inodes.extend(self.cover_inodes.read().await.get(url).cloned());
+                    }
+
                     let manga_ref = std::sync::Arc::downgrade(&manga);
                     self.new_node(manga_ino, fs::entry::Entry::Manga(manga_ref, directory)).await;
+                    self.set_time(&timed_inodes, manga.chapters.iter().map(|chapter| chapter.timestamp).max().unwrap_or(0u64)).await;
                     
-                    if let Some(fs::entry::Inode(fs::entry::Entry::Root(directory), _)) = self.entries.write().await.get_mut(&1u64) {
-                        directory.children.insert(manga.to_string().into(), (manga_ino, false));
+                    if let Some(fs::entry::Inode(fs::entry::Entry::Root(directory), attributes)) = self.entries.write().await.get_mut(&1u64) {
+                        let name = directory.unique_name(self.manga_name(manga.id, &manga.title), manga_ino);
+                        directory.children.insert(name, (manga_ino, false));
+                        attributes.set_time(std::time::SystemTime::now());
 
                         self.server.lock().await.notify_inval_inode(1u64, 0i64, 0i64).await.ok();
 
-                        Ok(GetOrFetchRef::Fetched(std::sync::Arc::downgrade(vacant.insert(manga))))
+                        Ok(manga)
                     }
                     else { panic!("root directory is gone?"); }
                 },
                 Err(error) => Err(error)
             }
+        }).await
+    }
+
+    // Chapters in the given languages are listed directly in the manga directory. With language directories
+    // enabled every language present gets its own subdirectory (named by its ISO 639-1 code) as well.
+    async fn make_manga_directory(&self, manga_ino: u64, manga: &fs::entry::Manga, languages: &[String]) -> fs::entry::Directory {
+        let mut directory = fs::entry::Directory::new(1u64);
+        let mut language_directories: std::collections::HashMap<String, (fs::entry::Directory, Vec<&fs::entry::ChapterShort>)> = std::collections::HashMap::default();
+        let mut listed = vec![];
+        let mut exported = vec![];
+
+        for chapter in manga.visible_chapters(&self.config.preferred_groups, &self.config.blocked_groups, self.config.collapse_chapters) {
+            let preferred = languages.iter().any(|lang| lang == &chapter.lang_code);
+
+            if !preferred && !self.config.language_directories {
+                continue;
+            }
+
+            let chapter_ino: u64 = self.make_next_ino().await;
+            self.new_node(chapter_ino, fs::entry::Entry::ChapterNotFetched(chapter.id)).await;
+            self.set_time(&[chapter_ino], chapter.timestamp).await;
+            self.chapters_inodes.write().await.insert(chapter.id, chapter_ino);
+
+            let name = self.chapter_name(&manga.title, &chapter.fields());
+
+            if preferred {
+                let path = directory.unique_name(name.clone(), chapter_ino);
+                directory.children.insert(path.clone(), (chapter_ino, false));
+                listed.push(chapter);
+                exported.push((path, chapter));
+            }
+
+            if self.config.language_directories {
+                let (language_directory, language_listed) = language_directories
+                    .entry(api::iso_639_1(&chapter.lang_code).to_owned())
+                    .or_insert_with(|| (fs::entry::Directory::new(manga_ino), vec![]));
+
+                language_directory.children.insert(language_directory.unique_name(name, chapter_ino), (chapter_ino, false));
+                language_listed.push(chapter);
+            }
+        }
+
+        // Pages of different languages don't make one read, so all/ holds the chapters of the first configured language there are any in.
+        if let Some(lang_code) = languages.iter().find(|lang| listed.iter().any(|chapter| &chapter.lang_code == *lang)) {
+            self.make_all_pages(&mut directory, listed.iter().cloned().filter(|chapter| &chapter.lang_code == lang_code).collect()).await;
+        }
+
+        self.make_first_latest_links(&mut directory, listed).await;
+        self.make_exports(&mut directory, exported, languages.len() > 1usize).await;
+
+        for (language, (mut language_directory, language_listed)) in language_directories {
+            let newest = language_listed.iter().map(|chapter| chapter.timestamp).max().unwrap_or(0u64);
+            self.make_all_pages(&mut language_directory, language_listed.clone()).await;
+            self.make_first_latest_links(&mut language_directory, language_listed).await;
+
+            let language_ino: u64 = self.make_next_ino().await;
+            directory.children.insert(directory.unique_name(language, language_ino), (language_ino, false));
+            self.new_node(language_ino, fs::entry::Entry::Directory(language_directory)).await;
+            self.set_time(&[language_ino], newest).await;
         }
+
+        directory
+    }
+
+    async fn make_all_pages(&self, directory: &mut fs::entry::Directory, chapters: Vec<&fs::entry::ChapterShort>) {
+        let newest = chapters.iter().map(|chapter| chapter.timestamp).max().unwrap_or(0u64);
+        let chapter_ids = fs::entry::reading_order(chapters).into_iter().map(|chapter| chapter.id).collect::<Vec<_>>();
+
+        let all_ino: u64 = self.make_next_ino().await;
+        directory.children.insert(directory.unique_name(String::from("all"), all_ino), (all_ino, false));
+        self.new_node(all_ino, fs::entry::Entry::AllPages(chapter_ids)).await;
+        self.set_time(&[all_ino], newest).await;
+    }
+
+    // Export files next to the chapter directories and, if enabled, one per volume and language.
+    async fn make_exports(&self, directory: &mut fs::entry::Directory, chapters: Vec<(std::path::PathBuf, &fs::entry::ChapterShort)>, multiple_languages: bool) {
+        let formats = self.config.exports.iter().filter_map(|format| fs::entry::ExportFormat::parse(format)).collect::<Vec<_>>();
+
+        if formats.is_empty() {
+            return;
+        }
+
+        let mut files = vec![];
+
+        for (path, chapter) in &chapters {
+            files.push((path.to_string_lossy().into_owned(), vec![chapter.id], chapter.timestamp));
+        }
+
+        if self.config.volume_exports {
+            let mut volumes: std::collections::BTreeMap<(String, String), Vec<&fs::entry::ChapterShort>> = std::collections::BTreeMap::default();
+
+            for (_, chapter) in &chapters {
+                if !chapter.volume.is_empty() {
+                    volumes.entry((chapter.volume.clone(), chapter.lang_code.clone())).or_default().push(chapter);
+                }
+            }
+
+            for ((volume, lang_code), volume_chapters) in volumes {
+                let name = match multiple_languages {
+                    true => format!("Vol. {} [{}]", volume, api::iso_639_1(&lang_code)),
+                    false => format!("Vol. {}", volume)
+                };
+
+                let newest = volume_chapters.iter().map(|chapter| chapter.timestamp).max().unwrap_or(0u64);
+                let chapter_ids = fs::entry::reading_order(volume_chapters).into_iter().map(|chapter| chapter.id).collect::<Vec<_>>();
+
+                if !chapter_ids.is_empty() {
+                    files.push((name, chapter_ids, newest));
+                }
+            }
+        }
+
+        for (name, chapter_ids, timestamp) in files {
+            for format in &formats {
+                let export_ino: u64 = self.make_next_ino().await;
+                directory.children.insert(directory.unique_name(format!("{}.{}", name, format.extension()), export_ino), (export_ino, true));
+                self.new_node(export_ino, fs::entry::Entry::Export(*format, chapter_ids.clone(), None)).await;
+                self.set_time(&[export_ino], timestamp).await;
+            }
+        }
+    }
+
+    async fn make_link(&self, directory: &mut fs::entry::Directory, name: &str, target: std::path::PathBuf) {
+        let link_ino: u64 = self.make_next_ino().await;
+        directory.children.insert(name.into(), (link_ino, true));
+        directory.links.insert(link_ino);
+        self.new_node(link_ino, fs::entry::Entry::Symlink(target)).await;
+    }
+
+    // Links to the first and the latest chapter listed in the directory, by chapter number.
+    async fn make_first_latest_links(&self, directory: &mut fs::entry::Directory, chapters: Vec<&fs::entry::ChapterShort>) {
+        let ordered = fs::entry::reading_order(chapters.into_iter().filter(|chapter| chapter.chapter.parse::<f64>().is_ok()));
+
+        for (name, chapter) in [("first", ordered.first()), ("latest", ordered.last())] {
+            let chapter_ino = match chapter {
+                Some(chapter) => self.chapter_ino(chapter.id).await,
+                None => None
+            };
+
+            let target = directory.children
+                .iter()
+                .find(|(_, (child_ino, _))| Some(*child_ino) == chapter_ino)
+                .map(|(child_name, _)| child_name.clone());
+
+            if let Some(target) = target {
+                self.make_link(directory, name, target).await;
+            }
+        }
+    }
+
+    // Links to the neighbouring chapters in the same language, next to the chapter where the manga directory lists it.
+    async fn make_next_prev_links(&self, directory: &mut fs::entry::Directory, chapter: &fs::entry::Chapter) {
+        let neighbours = match self.manga.read().await.get(&chapter.manga_id) {
+            Some(manga) => {
+                let ordered = fs::entry::reading_order(
+                    manga.visible_chapters(&self.config.preferred_groups, &self.config.blocked_groups, self.config.collapse_chapters)
+                        .into_iter()
+                        .filter(|other| other.lang_code == chapter.lang_code)
+                );
+
+                match ordered.iter().position(|other| other.id == chapter.id).or_else(|| ordered.iter().position(|other| other.chapter == chapter.chapter && other.volume == chapter.volume)) {
+                    Some(position) => vec![("prev", position.checked_sub(1usize)), ("next", Some(position + 1usize))]
+                        .into_iter()
+                        .filter_map(|(name, index)| index.and_then(|index| ordered.get(index)).map(|other| (name, other.id)))
+                        .collect::<Vec<_>>(),
+                    None => vec![]
+                }
+            },
+            None => vec![]
+        };
+
+        let manga_ino = match self.manga_ino(chapter.manga_id).await {
+            Some(manga_ino) => manga_ino,
+            None => return
+        };
+
+        let mut links = vec![];
+
+        for (name, other_id) in neighbours {
+            if let Some(other_ino) = self.chapter_ino(other_id).await {
+                if let Some(chapter_name) = self.listed_chapter_name(manga_ino, other_ino, &chapter.lang_code).await {
+                    links.push((name, std::path::Path::new("..").join(chapter_name)));
+                }
+            }
+        }
+
+        for (name, target) in links {
+            self.make_link(directory, name, target).await;
+        }
+    }
+
+    // The chapter's name in the manga directory, or else in its language directory.
+    async fn listed_chapter_name(&self, manga_ino: u64, chapter_ino: u64, lang_code: &str) -> Option<std::path::PathBuf> {
+        let entries = self.entries.read().await;
+
+        let manga_directory = match entries.get(&manga_ino) {
+            Some(fs::entry::Inode(fs::entry::Entry::Manga(_, directory), _)) => directory,
+            _ => return None
+        };
+
+        if let Some(chapter_name) = manga_directory.child_name(chapter_ino) {
+            return Some(chapter_name.clone());
+        }
+
+        let (language_ino, _) = manga_directory.children.get(std::path::Path::new(api::iso_639_1(lang_code)))?;
+
+        match entries.get(language_ino) {
+            Some(fs::entry::Inode(fs::entry::Entry::Directory(language_directory), _)) => language_directory.child_name(chapter_ino).cloned(),
+            _ => None
+        }
+    }
+
+    async fn make_chapter_directory(self: &std::sync::Arc<Context>, chapter: &std::sync::Arc<fs::entry::Chapter>) -> fs::entry::Directory {
+        let mut directory = fs::entry::Directory::new(1u64);
+
+        match &chapter.pages {
+            fs::entry::ChapterPages::Hosted(hosted) => {
+                let mut tasks = Vec::with_capacity(hosted.pages.len());
+
+                for (index, page) in hosted.pages.iter().enumerate() {
+                    let url = hosted.url.join(page).unwrap();
+
+                    let page_ino: u64 = self.make_next_ino().await;
+                    directory.children.insert(self.page_name(page, self.config.eink_pages), (page_ino, true));
+                    self.pages_inodes.write().await.insert(url.clone(), page_ino);
+
+                    debug!("fetching page {}/{} for chapter {}", index + 1, hosted.pages.len(), chapter.id);
+
+                    let self_ = self.clone();
+                    let chapter_id = chapter.id;
+                    let priority = if self.is_read_ahead(chapter_id).await {
+                        crate::scheduler::Priority::Prefetch
+                    }
+                    else if self.pinning.lock().await.contains(&chapter_id) {
+                        crate::scheduler::Priority::Background
+                    }
+                    else {
+                        crate::scheduler::Priority::Readdir
+                    };
+                    
+                    tasks.push(tokio::spawn(async move {
+                        match self_.downloads.acquire(priority, Some(chapter_id), &url).await {
+                            Some(ticket) => {
+                                if let Ok(page_ref) = self_.get_or_fetch_page(chapter_id, index, &url).await {
+                                    hold_until_complete(ticket, page_ref.get());
+                                }
+                            },
+                            None => debug!("skipping cancelled page {} of chapter {}", index + 1, chapter_id)
+                        }
+                    }));
+                }
+
+                for (index, task) in tasks.into_iter().enumerate() {
+                    debug!("awaiting page {}/{} for chapter {}", index + 1, hosted.pages.len(), chapter.id);
+                    task.await.ok();
+                }
+
+                if self.config.eink_view && !self.config.eink_pages {
+                    let eink_ino: u64 = self.make_next_ino().await;
+                    let mut eink_directory = fs::entry::Directory::new(1u64);
+
+                    for page in &hosted.pages {
+                        let url = hosted.url.join(page).unwrap();
+
+                        if let Some(page_ref) = self.pages.read().await.get(&url).map(std::sync::Arc::downgrade) {
+                            let page_ino: u64 = self.make_next_ino().await;
+                            eink_directory.children.insert(self.page_name(page, true), (page_ino, true));
+                            self.new_node(page_ino, fs::entry::Entry::EInkPage(page_ref, None)).await;
+                        }
+                    }
+
+                    let eink_children = eink_directory.children.values().map(|(child_ino, _)| *child_ino).collect::<Vec<_>>();
+                    self.set_time(&eink_children, chapter.timestamp).await;
+
+                    directory.children.insert("eink".into(), (eink_ino, false));
+                    self.new_node(eink_ino, fs::entry::Entry::Directory(eink_directory)).await;
+                }
+
+                if self.config.split_spreads {
+                    let split_ino: u64 = self.make_next_ino().await;
+                    directory.children.insert("split".into(), (split_ino, false));
+                    self.new_node(split_ino, fs::entry::Entry::SplitNotMade(chapter.id)).await;
+                }
+
+                if self.config.stitch_long_strips && chapter.long_strip {
+                    let strip_ino: u64 = self.make_next_ino().await;
+                    directory.children.insert("strip".into(), (strip_ino, false));
+                    self.new_node(strip_ino, fs::entry::Entry::StripNotStitched(chapter.id)).await;
+                }
+            },
+            fs::entry::ChapterPages::External(external) => {
+                let external_ino: u64 = self.make_next_ino().await;
+                directory.children.insert("external.html".into(), (external_ino, true));
+
+                let file = {
+                    let content = format!(
+                        r#"<!DOCTYPE HTML>
+                        <html>
+                        <head>
+                        <meta http-equiv="refresh" content="0; url={}" />
+                        </head>
+                        <body>
+                        </body>
+                        </html>"#,
+                        external.url.to_string()
+                    );
+            
+                    content.into_bytes()
+                };
+
+                self.new_node(external_ino, fs::entry::Entry::External(file)).await;
+                self.pages_inodes.write().await.insert(external.url.clone(), external_ino);
+            }
+        };
+
+        let comic_info_ino: u64 = self.make_next_ino().await;
+        directory.children.insert("ComicInfo.xml".into(), (comic_info_ino, true));
+        self.new_node(comic_info_ino, fs::entry::Entry::ComicInfo(self.comic_info(chapter).await)).await;
+        self.update_comic_info_when_complete(comic_info_ino, chapter.clone());
+
+        self.make_next_prev_links(&mut directory, chapter).await;
+
+        let children = directory.children.values().map(|(child_ino, _)| *child_ino).collect::<Vec<_>>();
+        self.set_time(&children, chapter.timestamp).await;
+
+        directory
+    }
+
+    async fn comic_info(&self, chapter: &fs::entry::Chapter) -> Vec<u8> {
+        let series = match self.manga.read().await.get(&chapter.manga_id) {
+            Some(manga) => manga.title.clone(),
+            None => String::default()
+        };
+
+        let dimensions = match &chapter.pages {
+            fs::entry::ChapterPages::Hosted(hosted) => {
+                let pages = self.pages.read().await;
+
+                hosted.pages
+                    .iter()
+                    .map(|page| hosted.url.join(page).ok().and_then(|url| pages.get(&url)).and_then(|page| page.dimensions()))
+                    .collect::<Vec<_>>()
+            },
+            fs::entry::ChapterPages::External(_) => vec![]
+        };
+
+        fs::comic_info::comic_info(&series, chapter, &dimensions)
+    }
+
+    // The chapter directory is made while its pages are still downloading, so the page dimensions
+    // in ComicInfo.xml are filled in once they have all arrived.
+    fn update_comic_info_when_complete(self: &std::sync::Arc<Context>, ino: u64, chapter: std::sync::Arc<fs::entry::Chapter>) {
+        let context = self.clone();
+
+        tokio::spawn(async move {
+            let pages = match &chapter.pages {
+                fs::entry::ChapterPages::Hosted(hosted) => {
+                    let cache = context.pages.read().await;
+
+                    hosted.pages
+                        .iter()
+                        .filter_map(|page| hosted.url.join(page).ok().and_then(|url| cache.get(&url).cloned()))
+                        .collect::<Vec<_>>()
+                },
+                fs::entry::ChapterPages::External(_) => return
+            };
+
+            for page in &pages {
+                page.data.complete().await;
+            }
+
+            let comic_info = context.comic_info(&chapter).await;
+
+            if let Some(fs::entry::Inode(fs::entry::Entry::ComicInfo(bytes), _)) = context.entries.write().await.get_mut(&ino) {
+                *bytes = comic_info;
+            }
+
+            context.server.lock().await.notify_inval_inode(ino, 0i64, 0i64).await.ok();
+        });
     }
 
     pub async fn get_or_fetch_chapter(self: &std::sync::Arc<Context>, id: u64) -> Result<GetOrFetchRef<fs::entry::Chapter>, api::GetMangaError> {
-        match self.chapters.write().await.entry(id) {
-            std::collections::hash_map::Entry::Occupied(occupied) => Ok(GetOrFetchRef::Cached(std::sync::Arc::downgrade(occupied.get()))),
-            std::collections::hash_map::Entry::Vacant(vacant) => match self.api.read().await.get_chapter(id).await {
+        self.get_or_make_chapter(id, None).await
+    }
+
+    // Like `get_or_fetch_chapter`, with the API response already at hand if `chapter_api` is given.
+    async fn get_or_make_chapter(self: &std::sync::Arc<Context>, id: u64, chapter_api: Option<api::Chapter>) -> Result<GetOrFetchRef<fs::entry::Chapter>, api::GetMangaError> {
+        self.chapters_in_flight.get_or_fetch(&self.chapters, id, || async move {
+            let result = match chapter_api {
+                Some(chapter_api) => Ok(chapter_api),
+                None => or_pinned(self.api.read().await.get_chapter(id).await, format!("chapter {}", id), self.store.load_chapter(id)).await
+            };
+
+            match result {
                 Ok(chapter_api) => {
                     let chapter = std::sync::Arc::new(fs::entry::Chapter::new(id, chapter_api));
 
+                    if self.manga_ino(chapter.manga_id).await.is_none() {
+                        debug!("manga {} of chapter {} is not loaded, fetching it", chapter.manga_id, id);
+                        self.get_or_fetch_manga(chapter.manga_id, self.config.languages.clone()).await?;
+                    }
+
                     match self.manga_inodes.read().await.get(&chapter.manga_id).cloned() {
                         Some(manga_ino) => {
                             let chapters_inodes_read_lock = self.chapters_inodes.read().await;
@@ -161,64 +742,15 @@ impl Context {
                                                 drop(entries_read_lock);
                                                 debug!("reusing chapter inode: {}", chapter_ino);
 
-                                                let mut directory = fs::entry::Directory::new(1u64);
-                                                match &chapter.pages {
-                                                    fs::entry::ChapterPages::Hosted(hosted) => {
-                                                        let mut tasks = Vec::with_capacity(hosted.pages.len());
-
-                                                        for (index, page) in hosted.pages.iter().enumerate() {
-                                                            let url = hosted.url.join(page).unwrap();
-
-                                                            let page_ino: u64 = self.make_next_ino().await;
-                                                            directory.children.insert(page.into(), (page_ino, true));
-                                                            self.pages_inodes.write().await.insert(url.clone(), page_ino);
-
-                                                            debug!("fetching page {}/{} for chapter {}", index + 1, hosted.pages.len(), id);
-
-                                                            let self_ = self.clone();
-                                                            let chapter_id = chapter.id;
-                                                            
-                                                            tasks.push(tokio::spawn(async move {
-                                                                self_.get_or_fetch_page(chapter_id, &url).await.ok();
-                                                            }));
-                                                        }
-
-                                                        for (index, task) in tasks.into_iter().enumerate() {
-                                                            debug!("awaiting page {}/{} for chapter {}", index + 1, hosted.pages.len(), id);
-                                                            task.await.ok();
-                                                        }
-                                                    },
-                                                    fs::entry::ChapterPages::External(external) => {
-                                                        let external_ino: u64 = self.make_next_ino().await;
-                                                        directory.children.insert("external.html".into(), (external_ino, true));
-                                    
-                                                        let file = {
-                                                            let content = format!(
-                                                                r#"<!DOCTYPE HTML>
-                                                                <html>
-                                                                <head>
-                                                                <meta http-equiv="refresh" content="0; url={}" />
-                                                                </head>
-                                                                <body>
-                                                                </body>
-                                                                </html>"#,
-                                                                external.url.to_string()
-                                                            );
-                                                    
-                                                            content.into_bytes()
-                                                        };
-
-                                                        self.new_node(external_ino, fs::entry::Entry::External(file)).await;
-                                                        self.pages_inodes.write().await.insert(external.url.clone(), external_ino);
-                                                    }
-                                                };
+                                                let directory = self.make_chapter_directory(&chapter).await;
 
                                                 let chapter_ref = std::sync::Arc::downgrade(&chapter);
                                                 self.new_node(chapter_ino, fs::entry::Entry::Chapter(chapter_ref, directory)).await;
+                                                self.set_time(&[chapter_ino], chapter.timestamp).await;
                                                 self.chapters_inodes.write().await.insert(chapter.id, chapter_ino);
                                                 self.server.lock().await.notify_inval_inode(chapter_ino, 0i64, 0i64).await.ok();
                 
-                                                Ok(GetOrFetchRef::Fetched(std::sync::Arc::downgrade(vacant.insert(chapter))))
+                                                Ok(chapter)
                                             }
                                             else {
                                                 panic!("cached chapter inode is not a ChapterNotFetched inode?");
@@ -232,68 +764,25 @@ impl Context {
                                     debug!("no chapter inode, creating a new one");
 
                                     let chapter_ino = self.make_next_ino().await;
-                        
-                                    let mut directory = fs::entry::Directory::new(1u64);
-                                    match &chapter.pages {
-                                        fs::entry::ChapterPages::Hosted(hosted) => {
-                                            let mut tasks = Vec::with_capacity(hosted.pages.len());
-
-                                            for (index, page) in hosted.pages.iter().enumerate() {
-                                                let url = hosted.url.join(page).unwrap();
-
-                                                let page_ino: u64 = self.make_next_ino().await;
-                                                directory.children.insert(page.into(), (page_ino, true));
-                                                self.pages_inodes.write().await.insert(url.clone(), page_ino);
-
-                                                debug!("fetching page {}/{} for chapter {}", index + 1, hosted.pages.len(), id);
-
-                                                let self_ = self.clone();
-                                                let chapter_id = chapter.id;
-                                                
-                                                tasks.push(tokio::spawn(async move {
-                                                    self_.get_or_fetch_page(chapter_id, &url).await.ok();
-                                                }));
-                                            }
+                                    let directory = self.make_chapter_directory(&chapter).await;
 
-                                            for (index, task) in tasks.into_iter().enumerate() {
-                                                debug!("awaiting page {}/{} for chapter {}", index + 1, hosted.pages.len(), id);
-                                                task.await.ok();
-                                            }
-                                        },
-                                        fs::entry::ChapterPages::External(external) => {
-                                            let external_ino: u64 = self.make_next_ino().await;
-                                            directory.children.insert("external.html".into(), (external_ino, true));
-                        
-                                            let file = {
-                                                let content = format!(
-                                                    r#"<!DOCTYPE HTML>
-                                                    <html>
-                                                    <head>
-                                                    <meta http-equiv="refresh" content="0; url={}" />
-                                                    </head>
-                                                    <body>
-                                                    </body>
-                                                    </html>"#,
-                                                    external.url.to_string()
-                                                );
-                                        
-                                                content.into_bytes()
-                                            };
-                        
-                                            self.new_node(external_ino, fs::entry::Entry::External(file)).await;
-                                        }
+                                    let manga_title = match self.manga.read().await.get(&chapter.manga_id) {
+                                        Some(manga) => manga.title.clone(),
+                                        None => String::default()
                                     };
+                                    let name = self.chapter_name(&manga_title, &chapter.fields());
 
                                     let chapter_ref = std::sync::Arc::downgrade(&chapter);
                                     self.new_node(chapter_ino, fs::entry::Entry::Chapter(chapter_ref, directory)).await;
+                                    self.set_time(&[chapter_ino], chapter.timestamp).await;
                                     self.chapters_inodes.write().await.insert(chapter.id, chapter_ino);
 
                                     if let Some(fs::entry::Inode(fs::entry::Entry::Manga(_, directory), _)) = self.entries.write().await.get_mut(&manga_ino) {
-                                        directory.children.insert(chapter.to_string().into(), (manga_ino, true));
+                                        directory.children.insert(directory.unique_name(name, chapter_ino), (chapter_ino, false));
 
                                         self.server.lock().await.notify_inval_inode(manga_ino, 0i64, 0i64).await.ok();
 
-                                        Ok(GetOrFetchRef::Fetched(std::sync::Arc::downgrade(vacant.insert(chapter))))
+                                        Ok(chapter)
                                     }
                                     else { panic!("manga inode not valid?"); }
                                 }
@@ -304,15 +793,48 @@ impl Context {
                 },
                 Err(error) => Err(error)
             }
-        }
+        }).await
     }
 
-    pub async fn get_or_fetch_page(&self, chapter_id: u64, url: &reqwest::Url) -> Result<GetOrFetchRef<fs::entry::Page>, api::GetPageError> {
-        match self.pages.write().await.entry(url.clone()) {
-            std::collections::hash_map::Entry::Occupied(occupied) => Ok(GetOrFetchRef::Cached(std::sync::Arc::downgrade(occupied.get()))),
-            std::collections::hash_map::Entry::Vacant(vacant) => match self.api.read().await.get_page(chapter_id, &url).await {
+    // Returns once the response headers are in, the page keeps filling in the background.
+    pub async fn get_or_fetch_page(self: &std::sync::Arc<Context>, chapter_id: u64, index: usize, url: &reqwest::Url) -> Result<GetOrFetchRef<fs::entry::Page>, api::GetPageError> {
+        {
+            // Checked and removed under one lock, a page refetched in between by someone else must stay.
+            let mut pages = self.pages.write().await;
+
+            if pages.get(url).map(|page| page.data.failed()).unwrap_or(false) {
+                debug!("refetching page {} of chapter {} after a failed download", index + 1, chapter_id);
+                pages.remove(url);
+            }
+        }
+
+        self.pages_in_flight.get_or_fetch(&self.pages, url.clone(), || async move {
+            let stored = async {
+                self.store.load_file(url).await.map(|data| api::Page::stored(chapter_id, data))
+            };
+
+            match or_pinned(self.api.read().await.get_page(chapter_id, url).await, format!("page {} of chapter {}", index + 1, chapter_id), stored).await {
                 Ok(page_api) => {
-                    let page = std::sync::Arc::new(fs::entry::Page(page_api.data));
+                    let page = std::sync::Arc::new(fs::entry::Page::new(chapter_id, index, url.clone(), page_api.length));
+
+                    let filled = page.clone();
+                    let context = self.clone();
+                    tokio::spawn(async move {
+                        match page_api.stream(|chunk| filled.data.push(chunk)).await {
+                            Ok(_) => {
+                                filled.data.finish();
+
+                                // Without a Content-Length the size reported so far was only what had arrived.
+                                if let Some(page_ino) = context.pages_inodes.read().await.get(&filled.url).cloned() {
+                                    context.server.lock().await.notify_inval_inode(page_ino, 0i64, 0i64).await.ok();
+                                }
+                            },
+                            Err(error) => {
+                                warn!("downloading page {} of chapter {} error: {}", filled.index + 1, filled.chapter_id, error);
+                                filled.data.fail();
+                            }
+                        }
+                    });
 
                     match self.chapters_inodes.read().await.get(&chapter_id).cloned() {
                         Some(chapter_ino) => {
@@ -325,10 +847,10 @@ impl Context {
 
                                     let page_ref = std::sync::Arc::downgrade(&page);
                                     
-                                    self.new_node(page_ino, fs::entry::Entry::Page(page_ref)).await;
+                                    self.new_node(page_ino, self.page_entry(page_ref)).await;
                                     self.server.lock().await.notify_inval_inode(chapter_ino, 0i64, 0i64).await.ok();
 
-                                    Ok(GetOrFetchRef::Fetched(std::sync::Arc::downgrade(vacant.insert(page))))
+                                    Ok(page)
                                 },
                                 None => {
                                     let page_ino = self.make_next_ino().await;
@@ -336,10 +858,10 @@ impl Context {
 
                                     let page_ref = std::sync::Arc::downgrade(&page);
                                     
-                                    self.new_node(page_ino, fs::entry::Entry::Page(page_ref)).await;
+                                    self.new_node(page_ino, self.page_entry(page_ref)).await;
                                     self.server.lock().await.notify_inval_inode(chapter_ino, 0i64, 0i64).await.ok();
     
-                                    Ok(GetOrFetchRef::Fetched(std::sync::Arc::downgrade(vacant.insert(page))))
+                                    Ok(page)
                                 }
                             }
                         },
@@ -348,13 +870,384 @@ impl Context {
                 },
                 Err(error) => Err(error)
             }
+        }).await
+    }
+
+    // Fetches the page through the download queue unless it's already there, and keeps its slot until it has finished streaming.
+    async fn get_or_fetch_page_queued(self: &std::sync::Arc<Context>, priority: crate::scheduler::Priority, chapter_id: u64, index: usize, url: &reqwest::Url) -> Result<Option<std::sync::Arc<fs::entry::Page>>, api::GetPageError> {
+        if let Some(page) = self.pages.read().await.get(url) {
+            if !page.data.failed() {
+                return Ok(Some(page.clone()));
+            }
+        }
+
+        let ticket = self.downloads.acquire(priority, None, url).await;
+        let page = self.get_or_fetch_page(chapter_id, index, url).await?.get();
+
+        if let Some(ticket) = ticket {
+            hold_until_complete(ticket, page.clone());
+        }
+
+        Ok(page.upgrade())
+    }
+
+    // Downloads the page again if its previous download failed, so that it isn't unreadable for good.
+    pub async fn refetch_if_failed(self: &std::sync::Arc<Context>, page: std::sync::Arc<fs::entry::Page>) -> Option<std::sync::Arc<fs::entry::Page>> {
+        if !page.data.failed() {
+            return Some(page);
+        }
+
+        {
+            // Only the failed page is dropped, not one someone else has refetched since.
+            let mut pages = self.pages.write().await;
+
+            if pages.get(&page.url).map(|cached| std::sync::Arc::ptr_eq(cached, &page)).unwrap_or(false) {
+                pages.remove(&page.url);
+            }
+        }
+
+        self.get_or_fetch_page_queued(crate::scheduler::Priority::Interactive, page.chapter_id, page.index, &page.url).await.ok().flatten()
+    }
+
+    // Lists the pages of the fetched chapters of an all/ directory. The others are fetched when one of their pages is looked up.
+    pub async fn make_all_pages_directory(&self, ino: u64, chapter_ids: &[u64]) -> fs::entry::Directory {
+        let mut directory = fs::entry::Directory::new(ino);
+        let entries = self.entries.read().await;
+
+        for (position, chapter_id) in chapter_ids.iter().enumerate() {
+            let chapter = self.chapters.read().await.get(chapter_id).cloned();
+
+            if let Some(fs::entry::ChapterPages::Hosted(hosted)) = chapter.as_ref().map(|chapter| &chapter.pages) {
+                for (index, page) in hosted.pages.iter().enumerate() {
+                    let name = self.page_name(page, self.config.eink_pages);
+                    let extension = name.extension().map(|extension| extension.to_string_lossy().into_owned()).unwrap_or_default();
+
+                    // Pages whose download was cancelled have an inode but no node until they're fetched again.
+                    if let Some(page_ino) = self.pages_inodes.read().await.get(&hosted.url.join(page).unwrap()).cloned().filter(|page_ino| entries.contains_key(page_ino)) {
+                        directory.children.insert(fs::naming::all_pages_name(position + 1usize, index + 1usize, &extension).into(), (page_ino, true));
+                    }
+                }
+            }
+        }
+
+        directory
+    }
+
+    // Finds the page inode behind an all/ name, fetching its chapter if needed.
+    pub async fn all_pages_lookup(self: &std::sync::Arc<Context>, chapter_ids: &[u64], name: &str) -> Result<Option<u64>, api::GetMangaError> {
+        let (position, page) = match fs::naming::parse_all_pages_name(name) {
+            Some((position, page)) if position > 0usize && page > 0usize => (position, page),
+            _ => return Ok(None)
+        };
+
+        let chapter_id = match chapter_ids.get(position - 1usize) {
+            Some(chapter_id) => *chapter_id,
+            None => return Ok(None)
+        };
+
+        let chapter = match self.get_or_fetch_chapter(chapter_id).await?.get().upgrade() {
+            Some(chapter) => chapter,
+            None => return Ok(None)
+        };
+
+        match &chapter.pages {
+            fs::entry::ChapterPages::Hosted(hosted) => match hosted.pages.get(page - 1usize) {
+                Some(name) => {
+                    let url = hosted.url.join(name).unwrap();
+                    let page_ino = self.pages_inodes.read().await.get(&url).cloned();
+
+                    match page_ino {
+                        Some(page_ino) if self.entries.read().await.contains_key(&page_ino) => Ok(Some(page_ino)),
+                        // The page's download was cancelled, fetching it again makes its node.
+                        _ => match self.get_or_fetch_page_queued(crate::scheduler::Priority::Interactive, chapter.id, page - 1usize, &url).await {
+                            Ok(_) => Ok(self.pages_inodes.read().await.get(&url).cloned()),
+                            Err(_) => Ok(None)
+                        }
+                    }
+                },
+                None => Ok(None)
+            },
+            fs::entry::ChapterPages::External(_) => Ok(None)
+        }
+    }
+
+    // Transcoded pages are served as JPEGs.
+    fn page_name(&self, page: &str, eink: bool) -> std::path::PathBuf {
+        match eink {
+            true => std::path::Path::new(page).with_extension("jpg"),
+            false => page.into()
         }
     }
 
+    fn page_entry(&self, page_ref: std::sync::Weak<fs::entry::Page>) -> fs::entry::Entry {
+        match self.config.eink_pages {
+            true => fs::entry::Entry::EInkPage(page_ref, None),
+            false => fs::entry::Entry::Page(page_ref)
+        }
+    }
+
+    // Transcodes the page behind an e-ink inode on its first access and points the inode at the result,
+    // so that its size is the transcoded one from then on. Pages that can't be transcoded are kept as they are.
+    pub async fn get_or_transcode_page(self: &std::sync::Arc<Context>, ino: u64) -> Option<std::sync::Arc<fs::entry::Transcoded>> {
+        let page = match self.entries.read().await.get(&ino) {
+            Some(fs::entry::Inode(fs::entry::Entry::EInkPage(page_ref, transcoded_ref), _)) => {
+                if let Some(transcoded) = transcoded_ref.as_ref().and_then(std::sync::Weak::upgrade) {
+                    return Some(transcoded);
+                }
+
+                page_ref.upgrade()?
+            },
+            _ => return None
+        };
+
+        let page = self.refetch_if_failed(page).await?;
+
+        let transcoded = self.transcoded_in_flight.get_or_fetch(&self.transcoded, page.url.clone(), || async {
+            let data = match page.data.complete().await {
+                Some(data) => data,
+                None => return Err(())
+            };
+
+            debug!("transcoding page {} of chapter {}", page.index, page.chapter_id);
+
+            let (width, height, contrast) = (self.config.eink_width, self.config.eink_height, self.config.eink_contrast);
+
+            let data = tokio::task::spawn_blocking(move || {
+                fs::eink::transcode(&data, width, height, contrast).unwrap_or_else(|| data.to_vec())
+            }).await;
+
+            match data {
+                Ok(data) => Ok(std::sync::Arc::new(fs::entry::Transcoded(data))),
+                Err(_) => Err(())
+            }
+        }).await.ok()?.get().upgrade()?;
+
+        self.new_node(ino, fs::entry::Entry::EInkPage(std::sync::Arc::downgrade(&page), Some(std::sync::Arc::downgrade(&transcoded)))).await;
+        self.server.lock().await.notify_inval_inode(ino, 0i64, 0i64).await.ok();
+
+        Some(transcoded)
+    }
+
+    // Stitches the slices of a long strip chapter when its strip directory is first accessed,
+    // the directory then becomes a plain directory of the stitched images.
+    pub async fn get_or_stitch_strip(self: &std::sync::Arc<Context>, strip_ino: u64, chapter_id: u64) -> Result<(), api::GetPageError> {
+        let chapter = match self.chapters.read().await.get(&chapter_id).cloned() {
+            Some(chapter) => chapter,
+            None => return Ok(())
+        };
+
+        self.strips_in_flight.get_or_fetch(&self.strips, chapter_id, || async move {
+            let mut pages = vec![];
+
+            if let fs::entry::ChapterPages::Hosted(hosted) = &chapter.pages {
+                for (index, page) in hosted.pages.iter().enumerate() {
+                    let url = hosted.url.join(page).unwrap();
+
+                    if let Some(page) = self.get_or_fetch_page_queued(crate::scheduler::Priority::Interactive, chapter_id, index, &url).await? {
+                        pages.extend(page.data.complete().await);
+                    }
+                }
+            }
+
+            debug!("stitching {} slices of chapter {}", pages.len(), chapter_id);
+
+            let strip_height = self.config.strip_height;
+            let images = tokio::task::spawn_blocking(move || {
+                let data = pages.iter().map(|data| data.as_slice()).collect::<Vec<_>>();
+                fs::strip::stitch(&data, strip_height)
+            }).await.unwrap_or_default();
+
+            let strip_images = images
+                .into_iter()
+                .enumerate()
+                .map(|(index, data)| std::sync::Arc::new(fs::entry::StripImage::new(chapter_id, index, data)))
+                .collect::<Vec<_>>();
+
+            let chapter_ino = self.chapter_ino(chapter_id).await.unwrap_or(1u64);
+            let mut directory = fs::entry::Directory::new(chapter_ino);
+
+            for strip_image in &strip_images {
+                let image_ino: u64 = self.make_next_ino().await;
+                directory.children.insert(format!("{:03}.jpg", strip_image.index + 1usize).into(), (image_ino, true));
+                self.new_node(image_ino, fs::entry::Entry::StripImage(std::sync::Arc::downgrade(strip_image))).await;
+            }
+
+            let children = directory.children.values().map(|(child_ino, _)| *child_ino).collect::<Vec<_>>();
+            self.set_time(&children, chapter.timestamp).await;
+
+            self.new_node(strip_ino, fs::entry::Entry::Directory(directory)).await;
+
+            Ok(std::sync::Arc::new(strip_images))
+        }).await.map(|_| ())
+    }
+
+    // Splits the double page spreads of a chapter when its split directory is first accessed. The directory
+    // then holds every page in reading order, with each spread replaced by its right and left halves.
+    pub async fn get_or_split_spreads(self: &std::sync::Arc<Context>, split_ino: u64, chapter_id: u64) -> Result<(), api::GetPageError> {
+        let chapter = match self.chapters.read().await.get(&chapter_id).cloned() {
+            Some(chapter) => chapter,
+            None => return Ok(())
+        };
+
+        self.spreads_in_flight.get_or_fetch(&self.spreads, chapter_id, || async move {
+            let chapter_ino = self.chapter_ino(chapter_id).await.unwrap_or(1u64);
+            let mut directory = fs::entry::Directory::new(chapter_ino);
+            let mut spread_halves = vec![];
+
+            if let fs::entry::ChapterPages::Hosted(hosted) = &chapter.pages {
+                for (index, page_name) in hosted.pages.iter().enumerate() {
+                    let url = hosted.url.join(page_name).unwrap();
+
+                    let page = match self.get_or_fetch_page_queued(crate::scheduler::Priority::Interactive, chapter_id, index, &url).await? {
+                        Some(page) => page,
+                        None => continue
+                    };
+
+                    let data = page.data.complete().await;
+
+                    let halves = match (page.is_spread(), data) {
+                        (true, Some(data)) => tokio::task::spawn_blocking(move || fs::spread::split(&data)).await.ok().flatten(),
+                        _ => None
+                    };
+
+                    match halves {
+                        Some((right, left)) => for data in [right, left] {
+                            let spread_half = std::sync::Arc::new(fs::entry::SpreadHalf::new(chapter_id, index, data));
+                            let half_ino: u64 = self.make_next_ino().await;
+
+                            directory.children.insert(format!("{:03}.jpg", directory.children.len() + 1usize).into(), (half_ino, true));
+                            self.new_node(half_ino, fs::entry::Entry::SpreadHalf(std::sync::Arc::downgrade(&spread_half))).await;
+                            spread_halves.push(spread_half);
+                        },
+                        None => {
+                            let extension = std::path::Path::new(page_name).extension().map(|extension| extension.to_string_lossy().into_owned()).unwrap_or_default();
+                            let name = format!("{:03}.{}", directory.children.len() + 1usize, extension);
+                            let page_ino: u64 = self.make_next_ino().await;
+
+                            directory.children.insert(self.page_name(&name, self.config.eink_pages), (page_ino, true));
+                            self.new_node(page_ino, self.page_entry(std::sync::Arc::downgrade(&page))).await;
+                        }
+                    }
+                }
+            }
+
+            let children = directory.children.values().map(|(child_ino, _)| *child_ino).collect::<Vec<_>>();
+            self.set_time(&children, chapter.timestamp).await;
+
+            self.new_node(split_ino, fs::entry::Entry::Directory(directory)).await;
+
+            Ok(std::sync::Arc::new(spread_halves))
+        }).await.map(|_| ())
+    }
+
+    // Builds the PDF or EPUB behind an export inode on its first access and points the inode at the result.
+    pub async fn get_or_build_export(self: &std::sync::Arc<Context>, ino: u64) -> Option<std::sync::Arc<fs::entry::Export>> {
+        let (format, chapter_ids) = match self.entries.read().await.get(&ino) {
+            Some(fs::entry::Inode(fs::entry::Entry::Export(format, chapter_ids, export_ref), _)) => {
+                if let Some(export) = export_ref.as_ref().and_then(std::sync::Weak::upgrade) {
+                    return Some(export);
+                }
+
+                (*format, chapter_ids.clone())
+            },
+            _ => return None
+        };
+
+        let key = (format, chapter_ids.clone());
+
+        let export = self.exports_in_flight.get_or_fetch(&self.exports, key, || async {
+            match self.build_export(format, &chapter_ids).await {
+                Some(data) => Ok(std::sync::Arc::new(fs::entry::Export(data))),
+                None => Err(())
+            }
+        }).await.ok()?.get().upgrade()?;
+
+        self.new_node(ino, fs::entry::Entry::Export(format, chapter_ids, Some(std::sync::Arc::downgrade(&export)))).await;
+        self.server.lock().await.notify_inval_inode(ino, 0i64, 0i64).await.ok();
+
+        Some(export)
+    }
+
+    // Builds the file from the chapters' pages, fetching what isn't fetched yet.
+    async fn build_export(self: &std::sync::Arc<Context>, format: fs::entry::ExportFormat, chapter_ids: &[u64]) -> Option<Vec<u8>> {
+        let mut chapters = vec![];
+        let mut pages = vec![];
+
+        for chapter_id in chapter_ids {
+            let chapter = match self.get_or_fetch_chapter(*chapter_id).await {
+                Ok(chapter_ref) => chapter_ref.get().upgrade()?,
+                Err(error) => {
+                    warn!("fetching chapter {} for export error: {}", chapter_id, error);
+                    return None;
+                }
+            };
+
+            if let fs::entry::ChapterPages::Hosted(hosted) = &chapter.pages {
+                for (index, page) in hosted.pages.iter().enumerate() {
+                    let url = hosted.url.join(page).unwrap();
+                    let page = self.get_or_fetch_page_queued(crate::scheduler::Priority::Interactive, *chapter_id, index, &url).await.ok().flatten()?;
+                    pages.push(page.data.complete().await?);
+                }
+            }
+
+            chapters.push(chapter);
+        }
+
+        let first = chapters.first()?;
+
+        let series = match self.manga.read().await.get(&first.manga_id) {
+            Some(manga) => manga.title.clone(),
+            None => String::default()
+        };
+
+        let metadata = match chapters.len() {
+            1usize => fs::export::Metadata {
+                identifier: format!("https://mangadex.org/chapter/{}", first.id),
+                title: match (first.title.is_empty(), first.chapter.is_empty()) {
+                    (false, _) => first.title.clone(),
+                    (true, false) => format!("Ch. {}", first.chapter),
+                    (true, true) => String::from("Oneshot")
+                },
+                series,
+                number: first.chapter.clone(),
+                language: api::iso_639_1(&first.lang_code).to_owned(),
+                timestamp: first.timestamp
+            },
+            _ => fs::export::Metadata {
+                identifier: format!("urn:mangadex:chapters:{}", chapter_ids.iter().map(u64::to_string).collect::<Vec<_>>().join(",")),
+                title: format!("Vol. {}", first.volume),
+                series,
+                number: first.volume.clone(),
+                language: api::iso_639_1(&first.lang_code).to_owned(),
+                timestamp: chapters.iter().map(|chapter| chapter.timestamp).max().unwrap_or(0u64)
+            }
+        };
+
+        debug!("building {} export of chapters {:?}", format.extension(), chapter_ids);
+
+        tokio::task::spawn_blocking(move || {
+            let data = pages.iter().map(|data| data.as_slice()).collect::<Vec<_>>();
+
+            match format {
+                fs::entry::ExportFormat::PDF => Some(fs::export::pdf(&metadata, &data)),
+                fs::entry::ExportFormat::EPUB => match fs::export::epub(&metadata, &data) {
+                    Ok(epub) => Some(epub),
+                    Err(error) => {
+                        warn!("building epub error: {}", error);
+                        None
+                    }
+                }
+            }
+        }).await.ok().flatten()
+    }
+
     pub async fn get_or_fetch_cover(&self, manga_id: u64, url: &reqwest::Url) -> Result<GetOrFetchRef<fs::entry::Cover>, api::GetCoverError> {
-        match self.covers.write().await.entry(url.clone()) {
-            std::collections::hash_map::Entry::Occupied(occupied) => Ok(GetOrFetchRef::Cached(std::sync::Arc::downgrade(occupied.get()))),
-            std::collections::hash_map::Entry::Vacant(vacant) => match self.api.read().await.get_cover(&url).await {
+        self.covers_in_flight.get_or_fetch(&self.covers, url.clone(), || async move {
+            let stored = async {
+                self.store.load_file(url).await.map(api::Cover)
+            };
+
+            match or_pinned(self.api.read().await.get_cover(url).await, format!("cover of manga {}", manga_id), stored).await {
                 Ok(cover_api) => {
                     let cover = std::sync::Arc::new(fs::entry::Cover(cover_api.0));
 
@@ -372,7 +1265,7 @@ impl Context {
                                     self.new_node(cover_ino, fs::entry::Entry::Cover(cover_ref)).await;
                                     self.server.lock().await.notify_inval_inode(manga_ino, 0i64, 0i64).await.ok();
 
-                                    Ok(GetOrFetchRef::Fetched(std::sync::Arc::downgrade(vacant.insert(cover))))
+                                    Ok(cover)
                                 },
                                 None => {
                                     let cover_ino = self.make_next_ino().await;
@@ -383,7 +1276,7 @@ impl Context {
                                     self.new_node(cover_ino, fs::entry::Entry::Cover(cover_ref)).await;
                                     self.server.lock().await.notify_inval_inode(manga_ino, 0i64, 0i64).await.ok();
     
-                                    Ok(GetOrFetchRef::Fetched(std::sync::Arc::downgrade(vacant.insert(cover))))
+                                    Ok(cover)
                                 }
                             }
                         },
@@ -392,9 +1285,574 @@ impl Context {
                 },
                 Err(error) => Err(error)
             }
+        }).await
+    }
+
+    pub fn manga_name(&self, id: u64, title: &str) -> String {
+        fs::naming::manga_name(self.config.manga_template.as_deref(), id, title)
+    }
+
+    pub fn chapter_name(&self, manga_title: &str, fields: &fs::naming::ChapterFields) -> String {
+        fs::naming::chapter_name(self.config.chapter_template.as_deref(), self.config.show_group_names, manga_title, fields)
+    }
+
+    pub async fn manga_ino(&self, id: u64) -> Option<u64> {
+        self.manga_inodes.read().await.get(&id).cloned()
+    }
+
+    pub async fn chapter_ino(&self, id: u64) -> Option<u64> {
+        self.chapters_inodes.read().await.get(&id).cloned()
+    }
+
+    fn remove_node_tree(entries: &mut std::collections::HashMap<u64, fs::entry::Inode>, ino: u64) {
+        let children = match entries.remove(&ino) {
+            Some(fs::entry::Inode(fs::entry::Entry::Manga(_, directory), _)) => directory.children,
+            Some(fs::entry::Inode(fs::entry::Entry::Chapter(_, directory), _)) => directory.children,
+            Some(fs::entry::Inode(fs::entry::Entry::Directory(directory), _)) => directory.children,
+            _ => std::collections::HashMap::default()
+        };
+
+        for (_, (child_ino, _)) in children {
+            Context::remove_node_tree(entries, child_ino);
+        }
+    }
+
+    pub async fn remove_manga(&self, id: u64) -> Option<std::sync::Arc<fs::entry::Manga>> {
+        let mut manga_map = self.manga.write().await;
+        let mut manga_inodes = self.manga_inodes.write().await;
+
+        // Both are removed or neither is, so the maps stay in step.
+        if !manga_map.contains_key(&id) || !manga_inodes.contains_key(&id) {
+            return None;
+        }
+
+        let manga = manga_map.remove(&id)?;
+        let manga_ino = manga_inodes.remove(&id)?;
+        drop(manga_map);
+        drop(manga_inodes);
+
+        let mut entries = self.entries.write().await;
+
+        let mut manga_name = None;
+
+        if let Some(fs::entry::Inode(fs::entry::Entry::Root(directory), attributes)) = entries.get_mut(&1u64) {
+            manga_name = directory.child_name(manga_ino).cloned();
+            directory.children.retain(|_, (child_ino, _)| *child_ino != manga_ino);
+            attributes.set_time(std::time::SystemTime::now());
+        }
+
+        Context::remove_node_tree(&mut entries, manga_ino);
+        drop(entries);
+
+        let mut chapters = self.chapters.write().await;
+        let mut chapters_inodes = self.chapters_inodes.write().await;
+        let mut pages = self.pages.write().await;
+        let mut pages_inodes = self.pages_inodes.write().await;
+        let mut transcoded = self.transcoded.write().await;
+
+        for chapter in &manga.chapters {
+            chapters_inodes.remove(&chapter.id);
+
+            if let Some(chapter) = chapters.remove(&chapter.id) {
+                match &chapter.pages {
+                    fs::entry::ChapterPages::Hosted(hosted) => for page in &hosted.pages {
+                        if let Ok(url) = hosted.url.join(page) {
+                            pages.remove(&url);
+                            pages_inodes.remove(&url);
+                            transcoded.remove(&url);
+                        }
+                    },
+                    fs::entry::ChapterPages::External(external) => {
+                        pages_inodes.remove(&external.url);
+                    }
+                }
+            }
+        }
+
+        drop(chapters);
+        drop(chapters_inodes);
+        drop(pages);
+        drop(pages_inodes);
+        drop(transcoded);
+
+        let mut strips = self.strips.write().await;
+        let mut spreads = self.spreads.write().await;
+        for chapter in &manga.chapters {
+            strips.remove(&chapter.id);
+            spreads.remove(&chapter.id);
+        }
+        drop(strips);
+        drop(spreads);
+
+        let chapter_ids = manga.chapters.iter().map(|chapter| chapter.id).collect::<std::collections::HashSet<_>>();
+        self.exports.write().await.retain(|(_, export_chapter_ids), _| !export_chapter_ids.iter().any(|id| chapter_ids.contains(id)));
+
+        if let Some(url) = &manga.cover {
+            self.covers.write().await.remove(url);
+            self.cover_inodes.write().await.remove(url);
+        }
+
+        self.server.lock().await.notify_inval_inode(1u64, 0i64, 0i64).await.ok();
+        if let Some(manga_name) = manga_name {
+            self.unindex_manga(&manga, &manga_name).await;
+        }
+
+        Some(manga)
+    }
+
+    // The by-genre, by-author and by-status keys the manga is listed under, in the order of `INDEX_INODES`.
+    fn index_keys(manga: &fs::entry::Manga) -> Vec<Vec<String>> {
+        vec![
+            manga.genres.iter().map(|genre| sanitize_filename::sanitize(genre.to_str())).collect(),
+            manga.creators().into_iter().map(sanitize_filename::sanitize).collect(),
+            vec![sanitize_filename::sanitize(manga.status.to_str().to_lowercase())]
+        ]
+    }
+
+    // Links the manga from its key directories in by-genre, by-author and by-status, creating the ones it's the first in.
+    // They link back to the manga directory in the root, listed as `manga_name`.
+    async fn index_manga(&self, manga: &fs::entry::Manga, manga_name: &std::path::Path) {
+        let mut changed = std::collections::HashSet::new();
+        let mut entries = self.entries.write().await;
+
+        for (index_ino, keys) in INDEX_INODES.iter().zip(Context::index_keys(manga)) {
+            for key in keys {
+                let existing = match entries.get(index_ino) {
+                    Some(fs::entry::Inode(fs::entry::Entry::Directory(index_directory), _)) => index_directory.children.get(std::path::Path::new(&key)).map(|(key_ino, _)| *key_ino),
+                    _ => continue
+                };
+
+                let key_ino = match existing {
+                    Some(key_ino) => key_ino,
+                    None => {
+                        let key_ino = self.make_next_ino().await;
+                        entries.insert(key_ino, fs::entry::Inode(fs::entry::Entry::Directory(fs::entry::Directory::new(*index_ino)), fs::entry::Attributes::new(key_ino, self.uid, self.gid, self.config.mode)));
+
+                        if let Some(fs::entry::Inode(fs::entry::Entry::Directory(index_directory), _)) = entries.get_mut(index_ino) {
+                            index_directory.children.insert(key.into(), (key_ino, false));
+                        }
+
+                        changed.insert(*index_ino);
+                        key_ino
+                    }
+                };
+
+                let link_ino = self.make_next_ino().await;
+                let target = std::path::Path::new("../..").join(manga_name);
+                entries.insert(link_ino, fs::entry::Inode(fs::entry::Entry::Symlink(target), fs::entry::Attributes::new(link_ino, self.uid, self.gid, self.config.mode)));
+
+                if let Some(fs::entry::Inode(fs::entry::Entry::Directory(key_directory), _)) = entries.get_mut(&key_ino) {
+                    key_directory.children.insert(manga_name.into(), (link_ino, true));
+                    key_directory.links.insert(link_ino);
+                }
+
+                changed.insert(key_ino);
+            }
+        }
+
+        drop(entries);
+
+        for ino in changed {
+            self.server.lock().await.notify_inval_inode(ino, 0i64, 0i64).await.ok();
+        }
+    }
+
+    // Drops the manga's links, and the key directories it was the last one in.
+    async fn unindex_manga(&self, manga: &fs::entry::Manga, manga_name: &std::path::Path) {
+        let mut changed = std::collections::HashSet::new();
+        let mut entries = self.entries.write().await;
+
+        for (index_ino, keys) in INDEX_INODES.iter().zip(Context::index_keys(manga)) {
+            for key in keys {
+                let key_ino = match entries.get(index_ino) {
+                    Some(fs::entry::Inode(fs::entry::Entry::Directory(index_directory), _)) => match index_directory.children.get(std::path::Path::new(&key)) {
+                        Some((key_ino, _)) => *key_ino,
+                        None => continue
+                    },
+                    _ => continue
+                };
+
+                let (link_ino, empty) = match entries.get_mut(&key_ino) {
+                    Some(fs::entry::Inode(fs::entry::Entry::Directory(key_directory), _)) => {
+                        let link_ino = key_directory.children.remove(manga_name).map(|(link_ino, _)| link_ino);
+
+                        if let Some(link_ino) = link_ino {
+                            key_directory.links.remove(&link_ino);
+                        }
+
+                        (link_ino, key_directory.children.is_empty())
+                    },
+                    _ => (None, false)
+                };
+
+                if let Some(link_ino) = link_ino {
+                    entries.remove(&link_ino);
+                }
+
+                if empty {
+                    entries.remove(&key_ino);
+
+                    if let Some(fs::entry::Inode(fs::entry::Entry::Directory(index_directory), _)) = entries.get_mut(index_ino) {
+                        index_directory.children.remove(std::path::Path::new(&key));
+                    }
+
+                    changed.insert(*index_ino);
+                }
+                else {
+                    changed.insert(key_ino);
+                }
+            }
+        }
+
+        drop(entries);
+
+        for ino in changed {
+            self.server.lock().await.notify_inval_inode(ino, 0i64, 0i64).await.ok();
+        }
+    }
+
+    pub async fn make_search_directory(&self, parent: u64, name: &std::ffi::OsStr, params: &api::SearchParams) -> Result<u64, api::APIError> {
+        let results = self.search(params).await?;
+        let directory_ino = self.make_next_ino().await;
+        let mut directory = fs::entry::Directory::new(parent);
+
+        for result in &results {
+            let child_ino = match self.manga_ino(result.id).await {
+                Some(manga_ino) => manga_ino,
+                None => {
+                    let manga_ino = self.make_next_ino().await;
+                    self.new_node(manga_ino, fs::entry::Entry::MangaNotFetched(result.id)).await;
+                    manga_ino
+                }
+            };
+
+            let name = directory.unique_name(self.manga_name(result.id, &result.title), child_ino);
+            directory.children.insert(name, (child_ino, false));
+        }
+
+        self.new_node(directory_ino, fs::entry::Entry::Directory(directory)).await;
+
+        if let Some(fs::entry::Inode(fs::entry::Entry::Search(search), _)) = self.entries.write().await.get_mut(&parent) {
+            search.children.insert(name.into(), (directory_ino, false));
+        }
+
+        self.server.lock().await.notify_inval_inode(parent, 0i64, 0i64).await.ok();
+
+        Ok(directory_ino)
+    }
+
+    pub async fn remove_search_directory(&self, parent: u64, name: &std::ffi::OsStr) -> bool {
+        let mut entries = self.entries.write().await;
+
+        let directory_ino = match entries.get_mut(&parent) {
+            Some(fs::entry::Inode(fs::entry::Entry::Search(search), _)) => match search.children.remove(std::path::Path::new(name)) {
+                Some((directory_ino, _)) => directory_ino,
+                None => return false
+            },
+            _ => return false
+        };
+
+        if let Some(fs::entry::Inode(fs::entry::Entry::Directory(directory), _)) = entries.remove(&directory_ino) {
+            for (_, (child_ino, _)) in directory.children {
+                if let Some(fs::entry::Inode(fs::entry::Entry::MangaNotFetched(_), _)) = entries.get(&child_ino) {
+                    entries.remove(&child_ino);
+                }
+            }
+        }
+
+        true
+    }
+
+    pub fn needs_refresh(&self, refreshed: Option<std::time::Instant>) -> bool {
+        match refreshed {
+            Some(refreshed) => refreshed.elapsed() >= std::time::Duration::from_secs(self.config.refresh_interval),
+            None => true
+        }
+    }
+
+    // Builds the new children of a refreshed listing. Children that are not loaded keep their previous
+    // inode when the name didn't change, otherwise they get a new inode with the given not fetched entry.
+    async fn make_listing(&self, ino: u64, parent: u64, children: Vec<(String, Option<u64>, fs::entry::Entry)>) -> fs::entry::Directory {
+        let mut previous = match self.entries.read().await.get(&ino) {
+            Some(fs::entry::Inode(fs::entry::Entry::Follows(directory, _), _)) => directory.children.clone(),
+            Some(fs::entry::Inode(fs::entry::Entry::MDList(_, directory, _), _)) => directory.children.clone(),
+            _ => std::collections::HashMap::default()
+        };
+
+        let mut directory = fs::entry::Directory::new(parent);
+
+        for (name, loaded_ino, not_fetched) in children {
+            let child_ino = match (loaded_ino, previous.remove(std::path::Path::new(&name))) {
+                (Some(loaded_ino), _) => loaded_ino,
+                (None, Some((previous_ino, _))) if self.entries.read().await.contains_key(&previous_ino) => previous_ino,
+                _ => {
+                    let child_ino = self.make_next_ino().await;
+                    self.new_node(child_ino, not_fetched).await;
+                    child_ino
+                }
+            };
+
+            directory.children.insert(directory.unique_name(name, child_ino), (child_ino, false));
+        }
+
+        let mut entries = self.entries.write().await;
+        let chapters_inodes = self.chapters_inodes.read().await;
+
+        for (_, (previous_ino, _)) in previous {
+            let unused = match entries.get(&previous_ino) {
+                Some(fs::entry::Inode(fs::entry::Entry::MangaNotFetched(_), _)) => true,
+                Some(fs::entry::Inode(fs::entry::Entry::ChapterNotFetched(chapter_id), _)) => chapters_inodes.get(chapter_id) != Some(&previous_ino),
+                _ => false
+            };
+
+            if unused && directory.children.values().all(|(child_ino, _)| *child_ino != previous_ino) {
+                entries.remove(&previous_ino);
+            }
+        }
+
+        directory
+    }
+
+    pub async fn refresh_follows(&self, ino: u64) -> Result<(), api::APIError> {
+        let follows = self.follows().await?;
+        let mut children = Vec::with_capacity(follows.len());
+
+        for entry in follows {
+            let fields = fs::naming::ChapterFields {
+                id: entry.chapter_id,
+                volume: &entry.chapter_volume,
+                chapter: &entry.chapter,
+                title: &entry.chapter_title,
+                lang_code: "",
+                groups: &[]
+            };
+
+            // The default chapter names don't contain the manga title, templates can place it themselves.
+            let name = match self.config.chapter_template {
+                Some(_) => self.chapter_name(&entry.manga_title, &fields),
+                None => sanitize_filename::sanitize(format!("{} - {}", entry.manga_title, self.chapter_name(&entry.manga_title, &fields)))
+            };
+
+            children.push((
+                name,
+                self.chapter_ino(entry.chapter_id).await,
+                fs::entry::Entry::ChapterNotFetched(entry.chapter_id)
+            ));
+        }
+
+        let directory = self.make_listing(ino, 1u64, children).await;
+
+        if let Some(fs::entry::Inode(fs::entry::Entry::Follows(follows, refreshed), _)) = self.entries.write().await.get_mut(&ino) {
+            *follows = directory;
+            *refreshed = Some(std::time::Instant::now());
+        }
+
+        self.server.lock().await.notify_inval_inode(ino, 0i64, 0i64).await.ok();
+
+        Ok(())
+    }
+
+    pub async fn refresh_mdlist(&self, ino: u64, status: api::MDListStatus) -> Result<(), api::APIError> {
+        let user_id = match *self.user_id.read().await {
+            Some(user_id) => user_id,
+            None => {
+                let user_id = self.api.read().await.user_id().await?;
+                *self.user_id.write().await = Some(user_id);
+                user_id
+            }
+        };
+
+        let params = api::MDListParams {
+            id: user_id,
+            status: Some(status),
+            ..api::MDListParams::default()
+        };
+
+        let results = self.mdlist(&params).await.map_err(api::APIError::Request)?;
+        let mut children = Vec::with_capacity(results.len());
+
+        for result in results {
+            children.push((
+                self.manga_name(result.id, &result.title),
+                self.manga_ino(result.id).await,
+                fs::entry::Entry::MangaNotFetched(result.id)
+            ));
+        }
+
+        let directory = self.make_listing(ino, MDLIST_INO, children).await;
+
+        if let Some(fs::entry::Inode(fs::entry::Entry::MDList(_, mdlist, refreshed), _)) = self.entries.write().await.get_mut(&ino) {
+            *mdlist = directory;
+            *refreshed = Some(std::time::Instant::now());
+        }
+
+        self.server.lock().await.notify_inval_inode(ino, 0i64, 0i64).await.ok();
+
+        Ok(())
+    }
+
+    // Queues the chapter for marking once its last page, or the configured fraction of its pages, has been read.
+    pub async fn page_read(&self, chapter_id: u64, index: usize) {
+        if self.marked_read.lock().await.contains(&chapter_id) {
+            return;
+        }
+
+        let page_count = match self.chapters.read().await.get(&chapter_id).map(|chapter| &chapter.pages) {
+            Some(fs::entry::ChapterPages::Hosted(hosted)) => hosted.pages.len(),
+            _ => return
+        };
+
+        let mut pages_read = self.pages_read.lock().await;
+        let read = pages_read.entry(chapter_id).or_default();
+        read.insert(index);
+
+        let threshold = (self.config.auto_mark_read_fraction * page_count as f64).ceil() as usize;
+
+        if index + 1usize == page_count || read.len() >= std::cmp::max(threshold, 1usize) {
+            pages_read.remove(&chapter_id);
+
+            if self.mark_read_queue.lock().await.insert(chapter_id) {
+                debug!("queued chapter {} for marking as read", chapter_id);
+            }
+        }
+    }
+
+    // Once the `read_ahead` fraction of a chapter's pages is read, fetches the next chapter in the same language
+    // in the background, at low priority. Opening a page of any chapter other than these two cancels it.
+    pub async fn read_ahead(self: std::sync::Arc<Context>, chapter_id: u64, index: usize) {
+        let fraction = match self.config.read_ahead {
+            Some(fraction) => fraction,
+            None => return
+        };
+
+        self.cancel_read_ahead(chapter_id).await;
+
+        if let Some(current) = self.read_ahead.lock().await.as_ref() {
+            if current.from == chapter_id {
+                return;
+            }
+        }
+
+        let chapter = match self.chapters.read().await.get(&chapter_id).cloned() {
+            Some(chapter) => chapter,
+            None => return
+        };
+
+        let page_count = match &chapter.pages {
+            fs::entry::ChapterPages::Hosted(hosted) => hosted.pages.len(),
+            fs::entry::ChapterPages::External(_) => return
+        };
+
+        let threshold = (fraction * page_count as f64).ceil() as usize;
+
+        if index + 1usize < std::cmp::max(threshold, 1usize) {
+            return;
+        }
+
+        let next_id = match self.next_chapter(&chapter).await {
+            Some(next_id) => next_id,
+            None => return
+        };
+
+        let mut read_ahead = self.read_ahead.lock().await;
+
+        match read_ahead.as_ref() {
+            Some(current) if current.from == chapter_id => return,
+            _ => *read_ahead = Some(ReadAhead { from: chapter_id, to: next_id })
+        };
+
+        drop(read_ahead);
+
+        debug!("reading ahead chapter {} after chapter {}", next_id, chapter_id);
+
+        if let Err(error) = self.get_or_fetch_chapter(next_id).await {
+            warn!("reading ahead chapter {} error: {}", next_id, error);
+        }
+    }
+
+    // Cancels the read ahead unless the chapter is the one being read or read ahead. Called when the reader
+    // opens or reads a page, listings and exports fetching chapters don't count as moving on.
+    pub async fn cancel_read_ahead(&self, chapter_id: u64) {
+        let mut read_ahead = self.read_ahead.lock().await;
+
+        if let Some(current) = read_ahead.as_ref() {
+            if current.from != chapter_id && current.to != chapter_id {
+                debug!("cancelling read ahead of chapter {}", current.to);
+                self.downloads.cancel(current.to);
+                *read_ahead = None;
+            }
+        }
+    }
+
+    async fn is_read_ahead(&self, chapter_id: u64) -> bool {
+        match self.read_ahead.lock().await.as_ref() {
+            Some(current) => current.to == chapter_id,
+            None => false
+        }
+    }
+
+    async fn next_chapter(&self, chapter: &fs::entry::Chapter) -> Option<u64> {
+        let manga = self.manga.read().await.get(&chapter.manga_id).cloned()?;
+
+        let ordered = fs::entry::reading_order(
+            manga.visible_chapters(&self.config.preferred_groups, &self.config.blocked_groups, self.config.collapse_chapters)
+                .into_iter()
+                .filter(|other| other.lang_code == chapter.lang_code)
+        );
+
+        let position = ordered.iter().position(|other| other.id == chapter.id).or_else(|| ordered.iter().position(|other| other.chapter == chapter.chapter && other.volume == chapter.volume))?;
+
+        ordered.get(position + 1usize).map(|next| next.id)
+    }
+
+    // Fetches a page of a fetched chapter by its name in the chapter directory.
+    pub async fn get_or_fetch_missing_page(self: &std::sync::Arc<Context>, chapter_id: u64, name: &std::ffi::OsStr) -> Option<u64> {
+        let chapter = self.chapters.read().await.get(&chapter_id).cloned()?;
+
+        if let fs::entry::ChapterPages::Hosted(hosted) = &chapter.pages {
+            for (index, page) in hosted.pages.iter().enumerate() {
+                if self.page_name(page, self.config.eink_pages).as_os_str() == name {
+                    let url = hosted.url.join(page).unwrap();
+
+                    // Whoever queued the page before doesn't get it ahead of this lookup.
+                    self.prioritise_page(&url);
+                    let ticket = self.downloads.acquire(crate::scheduler::Priority::Interactive, Some(chapter_id), &url).await?;
+                    hold_until_complete(ticket, self.get_or_fetch_page(chapter_id, index, &url).await.ok()?.get());
+
+                    return self.pages_inodes.read().await.get(&url).cloned();
+                }
+            }
+        }
+
+        None
+    }
+
+    // Sends the queued marks in batches, so a chapter read twice in a row is only marked once.
+    // Chapters that fail to be marked (logged out, offline) stay queued for the next batch.
+    pub async fn run_mark_read_queue(self: std::sync::Arc<Context>) {
+        loop {
+            tokio::time::delay_for(MARK_READ_INTERVAL).await;
+
+            let queued = self.mark_read_queue.lock().await.iter().cloned().collect::<Vec<_>>();
+
+            for chapter_id in queued {
+                match self.mark_chapter_read(chapter_id).await {
+                    Ok(()) => {
+                        info!("marked chapter {} as read", chapter_id);
+
+                        self.mark_read_queue.lock().await.remove(&chapter_id);
+                        self.marked_read.lock().await.insert(chapter_id);
+                    },
+                    Err(error) => debug!("marking chapter {} as read failed, keeping it queued: {:?}", chapter_id, error)
+                }
+            }
         }
     }
 
+    pub async fn invalidate_entry(&self, parent: u64, name: &std::ffi::OsStr) {
+        self.server.lock().await.notify_inval_entry(parent, name).await.ok();
+    }
+
     pub async fn search(&self, params: &api::SearchParams) -> Result<Vec<api::SearchEntry>, api::APIError> {
         self.api.read().await.search(params).await
     }
@@ -423,4 +1881,104 @@ impl Context {
     pub async fn follows(&self) -> Result<Vec<api::FollowsEntry>, api::APIError> {
         self.api.read().await.follows().await
     }
+
+    // Moves the page's queued download ahead of the others, the user is blocked on it.
+    pub fn prioritise_page(&self, url: &reqwest::Url) {
+        self.downloads.promote(url);
+    }
+
+    pub fn queue_status(&self) -> Vec<crate::scheduler::QueueStatus> {
+        self.downloads.status()
+    }
+
+    // Stores the manga and its cover, and returns the chapters to pin: those in the configured languages
+    // within the range of chapter numbers, in reading order.
+    pub async fn pin_manga(&self, id: u64, range: Option<(f64, f64)>) -> Result<Vec<u64>, crate::store::PinError> {
+        let raw = self.api.read().await.get_manga_raw(id).await.map_err(crate::store::PinError::Request)?;
+        let manga_api = api::Manga::from_raw(&raw);
+
+        // Only a response that parses is worth keeping, and it spares fetching the manga a second time.
+        if manga_api.is_some() {
+            self.store.save_manga(id, &raw).await.map_err(crate::store::PinError::IO)?;
+        }
+
+        let manga = match self.get_or_make_manga(id, self.config.languages.clone(), manga_api).await.map_err(crate::store::PinError::Request)?.get().upgrade() {
+            Some(manga) => manga,
+            None => return Ok(vec![])
+        };
+
+        if let Some(url) = &manga.cover {
+            if let Some(cover) = self.get_or_fetch_cover(id, url).await.map_err(crate::store::PinError::Request)?.get().upgrade() {
+                self.store.save_file(url, &cover.0).await.map_err(crate::store::PinError::IO)?;
+            }
+        }
+
+        let chapters = manga.visible_chapters(&self.config.preferred_groups, &self.config.blocked_groups, self.config.collapse_chapters)
+            .into_iter()
+            .filter(|chapter| self.config.languages.contains(&chapter.lang_code))
+            .filter(|chapter| match range {
+                Some((from, to)) => chapter.chapter.parse::<f64>().map(|number| number >= from && number <= to).unwrap_or(false),
+                None => true
+            });
+
+        Ok(fs::entry::reading_order(chapters).into_iter().map(|chapter| chapter.id).collect())
+    }
+
+    // Downloads the chapter and its pages at background priority and stores them. The manga counts as pinned
+    // once its first chapter is stored.
+    pub async fn pin_chapter(self: &std::sync::Arc<Context>, manga_id: u64, chapter_id: u64) -> Result<(), crate::store::PinError> {
+        let raw = self.api.read().await.get_chapter_raw(chapter_id).await.map_err(crate::store::PinError::Request)?;
+        let chapter_api = api::Chapter::from_raw(&raw);
+
+        if chapter_api.is_some() {
+            self.store.save_chapter(chapter_id, &raw).await.map_err(crate::store::PinError::IO)?;
+        }
+
+        self.pinning.lock().await.insert(chapter_id);
+        let chapter_ref = self.get_or_make_chapter(chapter_id, chapter_api).await;
+        self.pinning.lock().await.remove(&chapter_id);
+
+        let chapter = match chapter_ref.map_err(crate::store::PinError::Request)?.get().upgrade() {
+            Some(chapter) => chapter,
+            None => return Err(crate::store::PinError::Incomplete)
+        };
+
+        if let fs::entry::ChapterPages::Hosted(hosted) = &chapter.pages {
+            for (index, page) in hosted.pages.iter().enumerate() {
+                let url = hosted.url.join(page).unwrap();
+
+                let _ticket = self.downloads.acquire(crate::scheduler::Priority::Background, Some(chapter_id), &url).await;
+
+                let data = match self.get_or_fetch_page(chapter_id, index, &url).await.map_err(crate::store::PinError::Request)?.get().upgrade() {
+                    Some(page) => page.data.complete().await,
+                    None => None
+                };
+
+                match data {
+                    Some(data) => self.store.save_file(&url, &data).await.map_err(crate::store::PinError::IO)?,
+                    None => return Err(crate::store::PinError::Incomplete)
+                }
+            }
+        }
+
+        self.store.pin(manga_id, &[chapter_id]).await.map_err(crate::store::PinError::IO)
+    }
+
+    // Adds the pinned manga back to the mount, from the disk if MangaDex can't be reached.
+    pub async fn load_pinned(self: std::sync::Arc<Context>) {
+        for id in self.store.pinned().await {
+            match self.get_or_fetch_manga(id, self.config.languages.clone()).await {
+                Ok(_) => debug!("loaded pinned manga {}", id),
+                Err(error) => warn!("loading pinned manga {} error: {}", id, error)
+            }
+        }
+    }
+
+    pub async fn unpin_manga(&self, id: u64) -> std::io::Result<bool> {
+        self.store.unpin(id).await
+    }
+
+    pub async fn is_pinned(&self, manga_id: u64) -> bool {
+        self.store.is_pinned(manga_id).await
+    }
 }
\ No newline at end of file
@@ -0,0 +1,68 @@
+use crate::api;
+use crate::fs;
+
+// XML text and attribute values, also used by the EPUB exports.
+pub(crate) fn escape(text: &str) -> String {
+    text
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+// `pages` holds the dimensions of the chapter's pages, in order, where they are known.
+pub fn comic_info(series: &str, chapter: &fs::entry::Chapter, pages: &[Option<(u32, u32)>]) -> Vec<u8> {
+    let mut fields: Vec<(&str, String)> = vec![];
+
+    if !chapter.title.is_empty() { fields.push(("Title", chapter.title.clone())); }
+    fields.push(("Series", series.to_owned()));
+    if !chapter.chapter.is_empty() { fields.push(("Number", chapter.chapter.clone())); }
+    if let Ok(volume) = chapter.volume.parse::<u64>() { fields.push(("Volume", volume.to_string())); }
+
+    if chapter.timestamp > 0u64 {
+        let date = chrono::DateTime::<chrono::Utc>::from(std::time::UNIX_EPOCH + std::time::Duration::from_secs(chapter.timestamp));
+
+        fields.push(("Year", date.format("%Y").to_string()));
+        fields.push(("Month", date.format("%-m").to_string()));
+        fields.push(("Day", date.format("%-d").to_string()));
+    }
+
+    if !chapter.groups.is_empty() {
+        fields.push(("Translator", chapter.groups.iter().map(|(_, name)| name.as_str()).collect::<Vec<_>>().join(", ")));
+    }
+
+    fields.push(("Web", format!("https://mangadex.org/chapter/{}", chapter.id)));
+
+    match &chapter.pages {
+        fs::entry::ChapterPages::Hosted(hosted) => fields.push(("PageCount", hosted.pages.len().to_string())),
+        fs::entry::ChapterPages::External(_) => fields.push(("PageCount", 0usize.to_string()))
+    };
+
+    fields.push(("LanguageISO", api::iso_639_1(&chapter.lang_code).to_owned()));
+    fields.push(("Manga", "YesAndRightToLeft".to_owned()));
+
+    let pages = pages
+        .iter()
+        .enumerate()
+        .map(|(index, dimensions)| match dimensions {
+            Some((width, height)) if width > height => format!("    <Page Image=\"{}\" ImageWidth=\"{}\" ImageHeight=\"{}\" DoublePage=\"true\" />\n", index, width, height),
+            Some((width, height)) => format!("    <Page Image=\"{}\" ImageWidth=\"{}\" ImageHeight=\"{}\" />\n", index, width, height),
+            None => format!("    <Page Image=\"{}\" />\n", index)
+        })
+        .collect::<String>();
+
+    let content = format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<ComicInfo xmlns:xsd=\"http://www.w3.org/2001/XMLSchema\" xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\">\n{}{}</ComicInfo>\n",
+        fields
+            .into_iter()
+            .map(|(name, value)| format!("  <{0}>{1}</{0}>\n", name, escape(&value)))
+            .collect::<String>(),
+        match pages.is_empty() {
+            true => String::default(),
+            false => format!("  <Pages>\n{}  </Pages>\n", pages)
+        }
+    );
+
+    content.into_bytes()
+}
\ No newline at end of file
@@ -0,0 +1,31 @@
+// Converts a page to a grayscale JPEG that fits within `width`x`height`, keeping its aspect ratio.
+// Smaller pages are left at their size. A nonzero `contrast` is applied as with `image::imageops::contrast`.
+pub fn transcode(data: &[u8], width: u32, height: u32, contrast: f32) -> Option<Vec<u8>> {
+    let mut page = match image::load_from_memory(data) {
+        Ok(page) => page,
+        Err(error) => {
+            warn!("decoding page error: {}", error);
+            return None;
+        }
+    };
+
+    if image::GenericImageView::width(&page) > width || image::GenericImageView::height(&page) > height {
+        page = page.resize(width, height, image::imageops::FilterType::Triangle);
+    }
+
+    let mut page = page.to_luma8();
+
+    if contrast != 0f32 {
+        page = image::imageops::contrast(&page, contrast);
+    }
+
+    let mut transcoded = vec![];
+
+    match image::codecs::jpeg::JpegEncoder::new_with_quality(&mut transcoded, 85u8).encode(&page, page.width(), page.height(), image::ColorType::L8) {
+        Ok(()) => Some(transcoded),
+        Err(error) => {
+            warn!("encoding page error: {}", error);
+            None
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,129 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Streaming,
+    Complete,
+    Failed
+}
+
+#[derive(Debug)]
+struct State {
+    data: std::sync::Arc<Vec<u8>>,
+    length: Option<u64>,
+    status: Status
+}
+
+// File contents filled while they're being downloaded. Reads of what has arrived are answered right away,
+// reads past it wait for more.
+pub struct Buffer {
+    state: std::sync::RwLock<State>,
+    changed: tokio::sync::watch::Sender<()>,
+    watcher: tokio::sync::watch::Receiver<()>
+}
+
+impl std::fmt::Debug for Buffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.state.read().unwrap();
+
+        f.debug_struct("Buffer")
+            .field("received", &state.data.len())
+            .field("length", &state.length)
+            .field("status", &state.status)
+            .finish()
+    }
+}
+
+impl Buffer {
+    // `length` is the final size when it's known up front, from the Content-Length header.
+    pub fn new(length: Option<u64>) -> Buffer {
+        let (changed, watcher) = tokio::sync::watch::channel(());
+
+        Buffer {
+            state: std::sync::RwLock::new(State {
+                data: std::sync::Arc::default(),
+                length,
+                status: Status::Streaming
+            }),
+            changed,
+            watcher
+        }
+    }
+
+    pub fn push(&self, chunk: &[u8]) {
+        std::sync::Arc::make_mut(&mut self.state.write().unwrap().data).extend_from_slice(chunk);
+        self.changed.broadcast(()).ok();
+    }
+
+    pub fn finish(&self) {
+        {
+            let mut state = self.state.write().unwrap();
+            state.length = Some(state.data.len() as u64);
+            state.status = Status::Complete;
+        }
+
+        self.changed.broadcast(()).ok();
+    }
+
+    pub fn fail(&self) {
+        self.state.write().unwrap().status = Status::Failed;
+        self.changed.broadcast(()).ok();
+    }
+
+    pub fn failed(&self) -> bool {
+        self.state.read().unwrap().status == Status::Failed
+    }
+
+    // The final size once it's known, what has arrived so far until then.
+    pub fn size(&self) -> u64 {
+        let state = self.state.read().unwrap();
+
+        state.length.unwrap_or(state.data.len() as u64)
+    }
+
+    // Runs `f` over what has arrived so far.
+    pub fn peek<T, F: FnOnce(&[u8]) -> T>(&self, f: F) -> T {
+        f(&self.state.read().unwrap().data)
+    }
+
+    // Waits until `size` bytes from `offset` have arrived, or the download is complete.
+    // Returns `None` if the download fails first.
+    pub async fn read(&self, offset: usize, size: usize) -> Option<Vec<u8>> {
+        let mut watcher = self.watcher.clone();
+
+        loop {
+            {
+                let state = self.state.read().unwrap();
+                let end = offset.saturating_add(size);
+
+                if state.data.len() >= end || state.status == Status::Complete {
+                    let data = &state.data;
+                    return Some(data[std::cmp::min(offset, data.len())..std::cmp::min(end, data.len())].into());
+                }
+
+                if state.status == Status::Failed {
+                    return None;
+                }
+            }
+
+            watcher.recv().await;
+        }
+    }
+
+    // Waits for the whole contents, `None` if the download fails.
+    pub async fn complete(&self) -> Option<std::sync::Arc<Vec<u8>>> {
+        let mut watcher = self.watcher.clone();
+
+        loop {
+            {
+                let state = self.state.read().unwrap();
+
+                match state.status {
+                    Status::Complete => return Some(state.data.clone()),
+                    Status::Failed => return None,
+                    Status::Streaming => ()
+                }
+            }
+
+            watcher.recv().await;
+        }
+    }
+}
\ No newline at end of file
@@ -1,4 +1,5 @@
 use crate::api;
+use crate::fs;
 
 #[derive(Debug)]
 pub struct Hosted {
@@ -48,17 +49,46 @@ pub struct Chapter {
     pub volume: String,
     pub chapter: String,
     pub title: String,
+    pub lang_code: String,
+    pub groups: Vec<(u64, String)>,
+    pub timestamp: u64,
+    pub long_strip: bool,
     pub manga_id: u64,
     pub pages: ChapterPages
 }
 
+pub fn groups(groups: Vec<(u64, Option<String>)>) -> Vec<(u64, String)> {
+    groups
+        .into_iter()
+        .filter_map(|(group_id, group_name)| group_name.map(|group_name| (group_id, group_name)))
+        .filter(|(group_id, _)| *group_id != 0u64)
+        .collect()
+}
+
+// A group is matched by its id or, case insensitively, by its name.
+pub fn group_matches(pattern: &str, group: &(u64, String)) -> bool {
+    let (group_id, group_name) = group;
+
+    pattern == group_id.to_string() || pattern.to_lowercase() == group_name.to_lowercase()
+}
+
 impl Chapter {
     pub fn new(id: u64, chapter_api: api::Chapter) -> Chapter {
+        let groups = groups(vec![
+            (chapter_api.group_id, chapter_api.group_name),
+            (chapter_api.group_id_2, chapter_api.group_name_2),
+            (chapter_api.group_id_3, chapter_api.group_name_3)
+        ]);
+
         Chapter {
             id,
             volume: chapter_api.volume,
             chapter: chapter_api.chapter,
             title: chapter_api.title,
+            lang_code: chapter_api.lang_code,
+            groups,
+            timestamp: chapter_api.timestamp,
+            long_strip: chapter_api.long_strip,
             manga_id: chapter_api.manga_id,
             pages: match chapter_api.external {
                 Some(external) => ChapterPages::external(reqwest::Url::parse(&external).unwrap()),
@@ -75,21 +105,21 @@ impl Chapter {
     }
 }
 
+impl Chapter {
+    pub fn fields(&self) -> fs::naming::ChapterFields<'_> {
+        fs::naming::ChapterFields {
+            id: self.id,
+            volume: &self.volume,
+            chapter: &self.chapter,
+            title: &self.title,
+            lang_code: &self.lang_code,
+            groups: &self.groups
+        }
+    }
+}
+
 impl std::string::ToString for Chapter {
     fn to_string(&self) -> String {
-        if self.title == "Oneshot" {
-            format!("Oneshot [{}]", self.id)
-        }
-        else {
-            match (self.title.is_empty(), self.volume.is_empty()) {
-                (true, true) => sanitize_filename::sanitize(format!("Ch. {} [{}]", self.chapter, self.id)),
-                (true, false) => sanitize_filename::sanitize(format!("Vol. {} Ch. {} [{}]", self.volume, self.chapter, self.id)),
-                (false, true) => sanitize_filename::sanitize(format!("Ch. {} - {} [{}]", self.chapter, self.title, self.id)),
-                _ => sanitize_filename::sanitize(format!(
-                    "Vol. {} Ch. {} - {} [{}]",
-                    self.volume, self.chapter, self.title, self.id
-                )),
-            }
-        }
+        fs::naming::chapter_name(None, false, "", &self.fields())
     }
 }
\ No newline at end of file
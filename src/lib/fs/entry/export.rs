@@ -0,0 +1,28 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExportFormat {
+    PDF,
+    EPUB
+}
+
+impl ExportFormat {
+    pub fn parse(format: &str) -> Option<ExportFormat> {
+        match format.to_lowercase().as_str() {
+            "pdf" => Some(ExportFormat::PDF),
+            "epub" => Some(ExportFormat::EPUB),
+            _ => None
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::PDF => "pdf",
+            ExportFormat::EPUB => "epub"
+        }
+    }
+}
+
+// An export is identified by its format and the chapters it holds, in reading order.
+pub type ExportKey = (ExportFormat, Vec<u64>);
+
+#[derive(Debug)]
+pub struct Export(pub Vec<u8>);
\ No newline at end of file
@@ -1,4 +1,5 @@
 use crate::api;
+use crate::fs;
 
 #[derive(Debug)]
 pub struct ChapterShort {
@@ -6,7 +7,9 @@ pub struct ChapterShort {
     pub chapter: String,
     pub volume: String,
     pub title: String,
-    pub lang_code: String
+    pub lang_code: String,
+    pub groups: Vec<(u64, String)>,
+    pub timestamp: u64
 }
 
 impl ChapterShort {
@@ -16,27 +19,31 @@ impl ChapterShort {
             chapter: chapter_field.chapter,
             volume: chapter_field.volume,
             title: chapter_field.title,
-            lang_code: chapter_field.lang_code
+            lang_code: chapter_field.lang_code,
+            groups: super::groups(vec![
+                (chapter_field.group_id, chapter_field.group_name),
+                (chapter_field.group_id_2, chapter_field.group_name_2),
+                (chapter_field.group_id_3, chapter_field.group_name_3)
+            ]),
+            timestamp: chapter_field.timestamp as u64
+        }
+    }
+
+    pub fn fields(&self) -> fs::naming::ChapterFields<'_> {
+        fs::naming::ChapterFields {
+            id: self.id,
+            volume: &self.volume,
+            chapter: &self.chapter,
+            title: &self.title,
+            lang_code: &self.lang_code,
+            groups: &self.groups
         }
     }
 }
 
 impl std::string::ToString for ChapterShort {
     fn to_string(&self) -> String {
-        if self.title == "Oneshot" {
-            format!("Oneshot [{}]", self.id)
-        }
-        else {
-            match (self.title.is_empty(), self.volume.is_empty()) {
-                (true, true) => sanitize_filename::sanitize(format!("Ch. {} [{}]", self.chapter, self.id)),
-                (true, false) => sanitize_filename::sanitize(format!("Vol. {} Ch. {} [{}]", self.volume, self.chapter, self.id)),
-                (false, true) => sanitize_filename::sanitize(format!("Ch. {} - {} [{}]", self.chapter, self.title, self.id)),
-                _ => sanitize_filename::sanitize(format!(
-                    "Vol. {} Ch. {} - {} [{}]",
-                    self.volume, self.chapter, self.title, self.id
-                )),
-            }
-        }
+        fs::naming::chapter_name(None, false, "", &self.fields())
     }
 }
 
@@ -45,6 +52,10 @@ pub struct Manga {
     pub id: u64,
     pub title: String,
     pub cover: Option<reqwest::Url>,
+    pub genres: Vec<api::Genre>,
+    pub author: String,
+    pub artist: String,
+    pub status: api::MangaStatus,
     pub chapters: Vec<ChapterShort>,
 }
 
@@ -54,6 +65,10 @@ impl Manga {
             id,
             title: manga_api.manga.title,
             cover: reqwest::Url::parse("https://mangadex.org/").unwrap().join(&manga_api.manga.cover_url).ok(),
+            genres: manga_api.manga.genres,
+            author: manga_api.manga.author,
+            artist: manga_api.manga.artist,
+            status: manga_api.manga.status,
             chapters: manga_api.chapter
                 .into_iter()
                 .map(|(id, chapter)| ChapterShort::new(id, chapter))
@@ -62,8 +77,86 @@ impl Manga {
     }
 }
 
+impl Manga {
+    // Author and artist fields may hold several comma separated names.
+    pub fn creators(&self) -> Vec<String> {
+        let mut creators = vec![];
+
+        for name in self.author.split(',').chain(self.artist.split(',')).map(str::trim) {
+            if !name.is_empty() && !creators.iter().any(|creator| creator == name) {
+                creators.push(name.to_owned());
+            }
+        }
+
+        creators
+    }
+
+    // Drops chapters released by a blocked group. When collapsing, only one release per language,
+    // volume and chapter number is kept: the one by the earliest preferred group, then the oldest one.
+    pub fn visible_chapters(&self, preferred_groups: &[String], blocked_groups: &[String], collapse: bool) -> Vec<&ChapterShort> {
+        let rank = |chapter: &ChapterShort| preferred_groups
+            .iter()
+            .position(|pattern| chapter.groups.iter().any(|group| super::group_matches(pattern, group)))
+            .unwrap_or(preferred_groups.len());
+
+        let mut chapters = self.chapters
+            .iter()
+            .filter(|chapter| !chapter.groups.iter().any(|group| blocked_groups.iter().any(|pattern| super::group_matches(pattern, group))))
+            .collect::<Vec<_>>();
+
+        if collapse {
+            let mut kept: std::collections::HashMap<(&str, &str, &str), &ChapterShort> = std::collections::HashMap::default();
+            let mut uncollapsed = vec![];
+
+            for chapter in chapters {
+                if chapter.chapter.is_empty() {
+                    uncollapsed.push(chapter);
+                    continue;
+                }
+
+                let key = (chapter.lang_code.as_str(), chapter.volume.as_str(), chapter.chapter.as_str());
+
+                match kept.get(&key) {
+                    Some(other) if (rank(other), other.timestamp, other.id) <= (rank(chapter), chapter.timestamp, chapter.id) => (),
+                    _ => { kept.insert(key, chapter); }
+                }
+            }
+
+            chapters = kept.into_values().chain(uncollapsed).collect();
+        }
+
+        // The chapters come out of maps, sorting them keeps the names given to colliding chapters the same between runs.
+        chapters.sort_by_key(|chapter| chapter.id);
+
+        chapters
+    }
+}
+
+// Orders chapters by their chapter, then volume number, keeping one chapter per number. Chapters without
+// a numeric chapter number (oneshots, extras) come last, oldest first.
+pub fn reading_order<'a>(chapters: impl IntoIterator<Item = &'a ChapterShort>) -> Vec<&'a ChapterShort> {
+    let number = |text: &str| text.parse::<f64>().ok();
+
+    let mut ordered = vec![];
+    let mut unnumbered = vec![];
+
+    for chapter in chapters {
+        match number(&chapter.chapter) {
+            Some(number_chapter) => ordered.push(((number_chapter, number(&chapter.volume).unwrap_or(0f64)), chapter)),
+            None => unnumbered.push(chapter)
+        }
+    }
+
+    ordered.sort_by(|(key, chapter), (other_key, other)| key.partial_cmp(other_key).unwrap_or(std::cmp::Ordering::Equal).then(chapter.id.cmp(&other.id)));
+    ordered.dedup_by(|(key, _), (other_key, _)| key == other_key);
+
+    unnumbered.sort_by_key(|chapter| (chapter.timestamp, chapter.id));
+
+    ordered.into_iter().map(|(_, chapter)| chapter).chain(unnumbered).collect()
+}
+
 impl std::string::ToString for Manga {
     fn to_string(&self) -> String {
-        sanitize_filename::sanitize(format!("{} [{}]", self.title, self.id))
+        fs::naming::manga_name(None, self.id, &self.title)
     }
 }
\ No newline at end of file
@@ -1,12 +1,22 @@
 pub mod chapter;
 pub mod manga;
 pub mod page;
+pub mod buffer;
 pub mod cover;
+pub mod strip;
+pub mod transcoded;
+pub mod spread;
+pub mod export;
 
 pub use chapter::*;
 pub use manga::*;
 pub use page::*;
+pub use buffer::*;
 pub use cover::*;
+pub use strip::*;
+pub use transcoded::*;
+pub use spread::*;
+pub use export::*;
 
 #[derive(Debug)]
 pub struct Attributes {
@@ -15,26 +25,43 @@ pub struct Attributes {
     mtime: std::time::SystemTime,
     ctime: std::time::SystemTime,
     uid: nix::unistd::Uid,
-    gid: nix::unistd::Gid
+    gid: nix::unistd::Gid,
+    mode: Option<u32>
 }
 
 
 impl Attributes {
-    pub fn new(ino: u64, uid: nix::unistd::Uid, gid: nix::unistd::Gid) -> Attributes {
+    pub fn new(ino: u64, uid: nix::unistd::Uid, gid: nix::unistd::Gid, mode: Option<u32>) -> Attributes {
         Attributes {
             ino,
             atime: std::time::SystemTime::now(),
             mtime: std::time::SystemTime::now(),
             ctime: std::time::SystemTime::now(),
             uid,
-            gid
+            gid,
+            mode
         }
     }
+
+    pub fn set_time(&mut self, time: std::time::SystemTime) {
+        self.atime = time;
+        self.mtime = time;
+        self.ctime = time;
+    }
+}
+
+// MangaDex timestamps are seconds since the epoch, with 0 meaning unknown.
+pub fn timestamp_time(timestamp: u64) -> Option<std::time::SystemTime> {
+    match timestamp {
+        0u64 => None,
+        timestamp => Some(std::time::UNIX_EPOCH + std::time::Duration::from_secs(timestamp))
+    }
 }
 
 #[derive(Debug)]
 pub struct Directory {
     pub children: std::collections::HashMap<std::path::PathBuf, (u64, bool)>,
+    pub links: std::collections::HashSet<u64>,
     pub parent: Option<u64>,
 }
 
@@ -42,6 +69,7 @@ impl Directory {
     pub fn new(parent: u64) -> Directory {
         Directory {
             children: std::collections::HashMap::default(),
+            links: std::collections::HashSet::default(),
             parent: Some(parent)
         }
     }
@@ -49,16 +77,49 @@ impl Directory {
     pub fn root() -> Directory {
         Directory {
             children: std::collections::HashMap::default(),
+            links: std::collections::HashSet::default(),
             parent: None
         }
     }
 
+    // The name the child is listed under.
+    pub fn child_name(&self, ino: u64) -> Option<&std::path::PathBuf> {
+        self.children.iter().find(|(_, (child_ino, _))| *child_ino == ino).map(|(name, _)| name)
+    }
+
+    // Returns the name, or the name with a numbered suffix if another inode already uses it.
+    pub fn unique_name(&self, name: String, ino: u64) -> std::path::PathBuf {
+        let mut candidate = std::path::PathBuf::from(&name);
+        let mut counter = 2usize;
+
+        while let Some((other_ino, _)) = self.children.get(&candidate) {
+            if *other_ino == ino {
+                break;
+            }
+
+            candidate = format!("{} ({})", name, counter).into();
+            counter += 1usize;
+        }
+
+        candidate
+    }
+
+    // Sorted by name, so that the offsets of paginated readdir calls stay valid even for directories built anew
+    // on every call.
     pub fn entries(&self) -> Vec<polyfuse::DirEntry> {
-        self.children
-            .iter()
+        let mut children = self.children.iter().collect::<Vec<_>>();
+        children.sort_by_key(|(path, _)| *path);
+
+        children
+            .into_iter()
             .enumerate()
             .map(|(index, (path, (ino, is_file)))| {
-                if *is_file {
+                if self.links.contains(ino) {
+                    let mut direntry = polyfuse::DirEntry::new(path, *ino, index as u64 + 3u64);
+                    direntry.set_typ(libc::DT_LNK as u32);
+                    direntry
+                }
+                else if *is_file {
                     polyfuse::DirEntry::file(path, *ino, index as u64 + 3u64)
                 }
                 else {
@@ -70,6 +131,24 @@ impl Directory {
 }
 
 impl Attributes {
+    // Replaces the permission bits with the configured mode, never granting write access beyond the owner write
+    // of the directories taking mkdir. Directories are also searchable wherever they are readable, symlinks are left alone.
+    pub fn apply_mode(&self, mut attr: polyfuse::FileAttr) -> polyfuse::FileAttr {
+        if let Some(mode) = self.mode {
+            let file_type = attr.mode() & libc::S_IFMT;
+
+            let permissions = match file_type {
+                libc::S_IFLNK => attr.mode() & 0o777,
+                libc::S_IFDIR => ((mode | ((mode & 0o444) >> 2)) & 0o555) | (attr.mode() & 0o200),
+                _ => mode & 0o555
+            };
+
+            attr.set_mode(file_type | permissions);
+        }
+
+        attr
+    }
+
     pub fn file_attr(&self) -> polyfuse::FileAttr {
         let mut attr = polyfuse::FileAttr::default();
 
@@ -91,8 +170,24 @@ pub enum Entry {
     Chapter(std::sync::Weak<Chapter>, Directory),
     ChapterNotFetched(u64),
     Page(std::sync::Weak<Page>),
+    EInkPage(std::sync::Weak<Page>, Option<std::sync::Weak<Transcoded>>),
+    StripNotStitched(u64),
+    StripImage(std::sync::Weak<StripImage>),
+    SplitNotMade(u64),
+    SpreadHalf(std::sync::Weak<SpreadHalf>),
+    Export(ExportFormat, Vec<u64>, Option<std::sync::Weak<Export>>),
+    AllPages(Vec<u64>),
     Cover(std::sync::Weak<Cover>),
     External(Vec<u8>),
+    ComicInfo(Vec<u8>),
+    Directory(Directory),
+    ByIdManga,
+    ByIdChapter,
+    Search(Directory),
+    MangaNotFetched(u64),
+    Follows(Directory, Option<std::time::Instant>),
+    MDList(crate::api::MDListStatus, Directory, Option<std::time::Instant>),
+    Symlink(std::path::PathBuf),
     Root(Directory)
 }
 
@@ -103,8 +198,24 @@ impl Entry {
             Entry::Chapter(_, _) => "chapter",
             Entry::ChapterNotFetched(_) => "chapter (not fetched)",
             Entry::Page(_) => "page",
+            Entry::EInkPage(_, _) => "e-ink page",
+            Entry::StripNotStitched(_) => "strip (not stitched)",
+            Entry::StripImage(_) => "strip image",
+            Entry::SplitNotMade(_) => "split (not made)",
+            Entry::SpreadHalf(_) => "spread half",
+            Entry::Export(_, _, _) => "export",
+            Entry::AllPages(_) => "all pages",
             Entry::Cover(_) => "cover",
             Entry::External(_) => "external",
+            Entry::ComicInfo(_) => "comicinfo",
+            Entry::Directory(_) => "directory",
+            Entry::ByIdManga => "by-id manga",
+            Entry::ByIdChapter => "by-id chapter",
+            Entry::Search(_) => "search",
+            Entry::MangaNotFetched(_) => "manga (not fetched)",
+            Entry::Follows(_, _) => "follows",
+            Entry::MDList(_, _, _) => "mdlist",
+            Entry::Symlink(_) => "symlink",
             Entry::Root(_) => "root"
         }
     }
@@ -117,7 +228,7 @@ impl Inode {
     pub fn get_attr(&self) -> Option<polyfuse::FileAttr> {
         let (entry, attributes) = (&self.0, &self.1);
 
-        match entry {
+        let attr = match entry {
             Entry::Manga(manga_ref, _) => manga_ref.upgrade().map(|manga| {
                 let mut attr = attributes.file_attr();
 
@@ -141,7 +252,7 @@ impl Inode {
 
                 attr
             }),
-            Entry::ChapterNotFetched(_) => {
+            Entry::ChapterNotFetched(_) | Entry::StripNotStitched(_) | Entry::SplitNotMade(_) | Entry::AllPages(_) => {
                 let mut attr = attributes.file_attr();
 
                 attr.set_size(4096u64);
@@ -154,13 +265,65 @@ impl Inode {
             Entry::Page(page_ref) => page_ref.upgrade().map(|page| {
                 let mut attr = attributes.file_attr();
 
-                attr.set_size(page.0.len() as u64);
-                attr.set_blocks(1u64 + (page.0.len() as u64 / 512u64));
+                attr.set_size(page.data.size());
+                attr.set_blocks(1u64 + (page.data.size() / 512u64));
                 attr.set_mode(libc::S_IFREG as u32 | 0o444);
                 attr.set_nlink(1u32);
 
                 attr
             }),
+            // Until the page is transcoded its original size is reported.
+            Entry::EInkPage(page_ref, transcoded_ref) => page_ref.upgrade().map(|page| {
+                let mut attr = attributes.file_attr();
+
+                let size = match transcoded_ref.as_ref().and_then(std::sync::Weak::upgrade) {
+                    Some(transcoded) => transcoded.0.len() as u64,
+                    None => page.data.size()
+                };
+
+                attr.set_size(size);
+                attr.set_blocks(1u64 + (size / 512u64));
+                attr.set_mode(libc::S_IFREG | 0o444);
+                attr.set_nlink(1u32);
+
+                attr
+            }),
+            Entry::SpreadHalf(spread_half_ref) => spread_half_ref.upgrade().map(|spread_half| {
+                let mut attr = attributes.file_attr();
+
+                attr.set_size(spread_half.data.len() as u64);
+                attr.set_blocks(1u64 + (spread_half.data.len() as u64 / 512u64));
+                attr.set_mode(libc::S_IFREG | 0o444);
+                attr.set_nlink(1u32);
+
+                attr
+            }),
+            // Exports are built when first opened, until then they are empty.
+            Entry::Export(_, _, export_ref) => Some({
+                let mut attr = attributes.file_attr();
+
+                let size = match export_ref.as_ref().and_then(std::sync::Weak::upgrade) {
+                    Some(export) => export.0.len() as u64,
+                    None => 0u64
+                };
+
+                attr.set_size(size);
+                attr.set_blocks(1u64 + (size / 512u64));
+                attr.set_mode(libc::S_IFREG | 0o444);
+                attr.set_nlink(1u32);
+
+                attr
+            }),
+            Entry::StripImage(strip_image_ref) => strip_image_ref.upgrade().map(|strip_image| {
+                let mut attr = attributes.file_attr();
+
+                attr.set_size(strip_image.data.len() as u64);
+                attr.set_blocks(1u64 + (strip_image.data.len() as u64 / 512u64));
+                attr.set_mode(libc::S_IFREG | 0o444);
+                attr.set_nlink(1u32);
+
+                attr
+            }),
             Entry::Cover(page_ref) => page_ref.upgrade().map(|page| {
                 let mut attr = attributes.file_attr();
 
@@ -171,7 +334,7 @@ impl Inode {
 
                 attr
             }),
-            Entry::External(bytes) => Some({
+            Entry::External(bytes) | Entry::ComicInfo(bytes) => Some({
                 let mut attr = attributes.file_attr();
 
                 attr.set_size(bytes.len() as u64);
@@ -181,7 +344,39 @@ impl Inode {
 
                 attr
             }),
-            Entry::Root(directory) => {
+            Entry::Symlink(target) => Some({
+                let mut attr = attributes.file_attr();
+                let len = target.as_os_str().len() as u64;
+
+                attr.set_size(len);
+                attr.set_blocks(1u64 + (len / 512u64));
+                attr.set_mode(libc::S_IFLNK | 0o777);
+                attr.set_nlink(1u32);
+
+                attr
+            }),
+            Entry::ByIdManga | Entry::ByIdChapter | Entry::MangaNotFetched(_) => {
+                let mut attr = attributes.file_attr();
+
+                attr.set_size(4096u64);
+                attr.set_blocks(8u64);
+                attr.set_mode(libc::S_IFDIR | 0o555);
+                attr.set_nlink(2u32);
+
+                Some(attr)
+            },
+            // Manga and searches are added and removed with mkdir and rmdir, so these two are writable by the owner.
+            Entry::Root(directory) | Entry::Search(directory) => {
+                let mut attr = attributes.file_attr();
+
+                attr.set_size(4096u64);
+                attr.set_blocks(8u64);
+                attr.set_mode(libc::S_IFDIR | 0o755);
+                attr.set_nlink(2u32 + directory.children.len() as u32);
+                
+                Some(attr)
+            },
+            Entry::Directory(directory) | Entry::Follows(directory, _) | Entry::MDList(_, directory, _) => {
                 let mut attr = attributes.file_attr();
 
                 attr.set_size(4096u64);
@@ -191,6 +386,8 @@ impl Inode {
                 
                 Some(attr)
             }
-        }
+        };
+
+        attr.map(|attr| attributes.apply_mode(attr))
     }
 }
\ No newline at end of file
@@ -1,2 +1,41 @@
 #[derive(Debug)]
-pub struct Page(pub Vec<u8>);
\ No newline at end of file
+pub struct Page {
+    pub chapter_id: u64,
+    pub index: usize,
+    pub url: reqwest::Url,
+    pub data: super::Buffer
+}
+
+// Reads the width and height from the image header, without decoding the image.
+pub fn dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    image::io::Reader::new(std::io::Cursor::new(data))
+        .with_guessed_format()
+        .ok()?
+        .into_dimensions()
+        .ok()
+}
+
+impl Page {
+    // The data is pushed into the page as it's downloaded.
+    pub fn new(chapter_id: u64, index: usize, url: reqwest::Url, length: Option<u64>) -> Page {
+        Page {
+            chapter_id,
+            index,
+            url,
+            data: super::Buffer::new(length)
+        }
+    }
+
+    // Known as soon as the image header has arrived.
+    pub fn dimensions(&self) -> Option<(u32, u32)> {
+        self.data.peek(dimensions)
+    }
+
+    // A page wider than it is tall is taken for a double page spread.
+    pub fn is_spread(&self) -> bool {
+        match self.dimensions() {
+            Some((width, height)) => width > height,
+            None => false
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,16 @@
+#[derive(Debug)]
+pub struct SpreadHalf {
+    pub chapter_id: u64,
+    pub page_index: usize,
+    pub data: Vec<u8>
+}
+
+impl SpreadHalf {
+    pub fn new(chapter_id: u64, page_index: usize, data: Vec<u8>) -> SpreadHalf {
+        SpreadHalf {
+            chapter_id,
+            page_index,
+            data
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,16 @@
+#[derive(Debug)]
+pub struct StripImage {
+    pub chapter_id: u64,
+    pub index: usize,
+    pub data: Vec<u8>
+}
+
+impl StripImage {
+    pub fn new(chapter_id: u64, index: usize, data: Vec<u8>) -> StripImage {
+        StripImage {
+            chapter_id,
+            index,
+            data
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,2 @@
+#[derive(Debug)]
+pub struct Transcoded(pub Vec<u8>);
\ No newline at end of file
@@ -0,0 +1,202 @@
+use crate::fs;
+
+// Everything is derived from the chapters and their pages, so exporting the same chapters twice gives the same bytes.
+pub struct Metadata {
+    pub identifier: String,
+    pub title: String,
+    pub series: String,
+    pub number: String,
+    pub language: String,
+    pub timestamp: u64
+}
+
+// PDF text strings, as UTF-16BE with a byte order mark.
+fn pdf_string(text: &str) -> String {
+    let mut string = String::from("<FEFF");
+
+    for unit in text.encode_utf16() {
+        string.push_str(&format!("{:04X}", unit));
+    }
+
+    string.push('>');
+    string
+}
+
+fn jpeg(data: &[u8]) -> Option<(u32, u32, Vec<u8>)> {
+    let page = match image::load_from_memory(data) {
+        Ok(page) => page.to_rgb8(),
+        Err(error) => {
+            warn!("decoding page error: {}", error);
+            return None;
+        }
+    };
+
+    let mut jpeg = vec![];
+
+    match image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, 95u8).encode(&page, page.width(), page.height(), image::ColorType::Rgb8) {
+        Ok(()) => Some((page.width(), page.height(), jpeg)),
+        Err(error) => {
+            warn!("encoding page error: {}", error);
+            None
+        }
+    }
+}
+
+// One page per image, each the size of its image. Images are embedded as JPEGs.
+pub fn pdf(metadata: &Metadata, pages: &[&[u8]]) -> Vec<u8> {
+    let images = pages.iter().filter_map(|data| jpeg(data)).collect::<Vec<_>>();
+
+    // 1 catalog, 2 page tree, 3 document information, then a page, its image and its contents per image.
+    let mut objects: Vec<Vec<u8>> = vec![
+        format!("<< /Type /Catalog /Pages 2 0 R /Lang {} /ViewerPreferences << /Direction /R2L >> >>", pdf_string(&metadata.language)).into_bytes(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            (0..images.len()).map(|index| format!("{} 0 R", 4usize + 3usize * index)).collect::<Vec<_>>().join(" "),
+            images.len()
+        ).into_bytes(),
+        format!(
+            "<< /Title {} /Subject {} /Keywords {} /Source {} /Creator (mangadex-fs) >>",
+            pdf_string(&metadata.title),
+            pdf_string(&metadata.series),
+            pdf_string(&metadata.number),
+            pdf_string(&metadata.identifier)
+        ).into_bytes()
+    ];
+
+    for (index, (width, height, jpeg)) in images.iter().enumerate() {
+        let page_object = 4usize + 3usize * index;
+
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {0} {1}] /Resources << /XObject << /Im0 {2} 0 R >> >> /Contents {3} 0 R >>",
+            width, height, page_object + 1usize, page_object + 2usize
+        ).into_bytes());
+
+        let mut image_object = format!(
+            "<< /Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter /DCTDecode /Length {} >>\nstream\n",
+            width, height, jpeg.len()
+        ).into_bytes();
+        image_object.extend_from_slice(jpeg);
+        image_object.extend_from_slice(b"\nendstream");
+        objects.push(image_object);
+
+        let contents = format!("q {} 0 0 {} 0 0 cm /Im0 Do Q", width, height);
+        objects.push(format!("<< /Length {} >>\nstream\n{}\nendstream", contents.len(), contents).into_bytes());
+    }
+
+    let mut pdf = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n", index + 1usize).as_bytes());
+        pdf.extend_from_slice(object);
+        pdf.extend_from_slice(b"\nendobj\n");
+    }
+
+    let xref = pdf.len();
+    pdf.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1usize).as_bytes());
+
+    for offset in offsets {
+        pdf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+
+    pdf.extend_from_slice(format!("trailer\n<< /Size {} /Root 1 0 R /Info 3 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1usize, xref).as_bytes());
+
+    pdf
+}
+
+struct EpubImage<'a> {
+    width: u32,
+    height: u32,
+    extension: &'static str,
+    media_type: &'static str,
+    data: std::borrow::Cow<'a, [u8]>
+}
+
+// Images EPUB readers support are stored as they are, anything else is converted to JPEG.
+fn epub_image(data: &[u8]) -> Option<EpubImage<'_>> {
+    let (extension, media_type) = match image::guess_format(data) {
+        Ok(image::ImageFormat::Jpeg) => ("jpg", "image/jpeg"),
+        Ok(image::ImageFormat::Png) => ("png", "image/png"),
+        Ok(image::ImageFormat::Gif) => ("gif", "image/gif"),
+        _ => return jpeg(data).map(|(width, height, jpeg)| EpubImage { width, height, extension: "jpg", media_type: "image/jpeg", data: jpeg.into() })
+    };
+
+    match fs::entry::dimensions(data) {
+        Some((width, height)) => Some(EpubImage { width, height, extension, media_type, data: data.into() }),
+        None => jpeg(data).map(|(width, height, jpeg)| EpubImage { width, height, extension: "jpg", media_type: "image/jpeg", data: jpeg.into() })
+    }
+}
+
+// A fixed layout EPUB 3 with one page per image, read right to left.
+pub fn epub(metadata: &Metadata, pages: &[&[u8]]) -> zip::result::ZipResult<Vec<u8>> {
+    let images = pages.iter().filter_map(|data| epub_image(data)).collect::<Vec<_>>();
+
+    let modified = chrono::DateTime::<chrono::Utc>::from(std::time::UNIX_EPOCH + std::time::Duration::from_secs(metadata.timestamp));
+
+    let mut manifest = String::from("    <item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\" />\n");
+    let mut spine = String::default();
+    let mut files: Vec<(String, Vec<u8>)> = vec![];
+
+    for (index, image) in images.iter().enumerate() {
+        let number = index + 1usize;
+        let EpubImage { width, height, extension, media_type, data } = image;
+
+        manifest.push_str(&format!("    <item id=\"image{0:03}\" href=\"images/{0:03}.{1}\" media-type=\"{2}\" />\n", number, extension, media_type));
+        manifest.push_str(&format!("    <item id=\"page{0:03}\" href=\"pages/{0:03}.xhtml\" media-type=\"application/xhtml+xml\" />\n", number));
+        spine.push_str(&format!("    <itemref idref=\"page{:03}\" />\n", number));
+
+        let page = format!(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<!DOCTYPE html>\n<html xmlns=\"http://www.w3.org/1999/xhtml\">\n<head>\n  <title>{0}</title>\n  <meta name=\"viewport\" content=\"width={1}, height={2}\" />\n  <style>body {{ margin: 0; }} img {{ display: block; width: 100%; height: 100%; }}</style>\n</head>\n<body>\n  <img src=\"../images/{3:03}.{4}\" alt=\"{3}\" />\n</body>\n</html>\n",
+            fs::comic_info::escape(&metadata.title), width, height, number, extension
+        );
+
+        files.push((format!("OEBPS/images/{:03}.{}", number, extension), data.to_vec()));
+        files.push((format!("OEBPS/pages/{:03}.xhtml", number), page.into_bytes()));
+    }
+
+    let content = format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" unique-identifier=\"identifier\" xml:lang=\"{language}\">\n  <metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n    <dc:identifier id=\"identifier\">{identifier}</dc:identifier>\n    <dc:title>{title}</dc:title>\n    <dc:language>{language}</dc:language>\n    <meta property=\"dcterms:modified\">{modified}</meta>\n    <meta property=\"belongs-to-collection\" id=\"series\">{series}</meta>\n    <meta refines=\"#series\" property=\"collection-type\">series</meta>\n    <meta refines=\"#series\" property=\"group-position\">{number}</meta>\n    <meta property=\"rendition:layout\">pre-paginated</meta>\n  </metadata>\n  <manifest>\n{manifest}  </manifest>\n  <spine page-progression-direction=\"rtl\">\n{spine}  </spine>\n</package>\n",
+        language = fs::comic_info::escape(&metadata.language),
+        identifier = fs::comic_info::escape(&metadata.identifier),
+        title = fs::comic_info::escape(&metadata.title),
+        modified = modified.format("%Y-%m-%dT%H:%M:%SZ"),
+        series = fs::comic_info::escape(&metadata.series),
+        number = fs::comic_info::escape(&metadata.number),
+        manifest = manifest,
+        spine = spine
+    );
+
+    let nav = format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<!DOCTYPE html>\n<html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\">\n<head>\n  <title>{0}</title>\n</head>\n<body>\n  <nav epub:type=\"toc\">\n    <ol>\n      <li><a href=\"pages/001.xhtml\">{0}</a></li>\n    </ol>\n  </nav>\n</body>\n</html>\n",
+        fs::comic_info::escape(&metadata.title)
+    );
+
+    let container = "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<container version=\"1.0\" xmlns=\"urn:oasis:names:tc:opendocument:xmlns:container\">\n  <rootfiles>\n    <rootfile full-path=\"OEBPS/content.opf\" media-type=\"application/oebps-package+xml\" />\n  </rootfiles>\n</container>\n";
+
+    let options = zip::write::FileOptions::default()
+        .compression_method(zip::CompressionMethod::Stored)
+        .last_modified_time(zip::DateTime::default());
+
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(vec![]));
+
+    // The mimetype has to come first, uncompressed.
+    writer.start_file("mimetype", options)?;
+    std::io::Write::write_all(&mut writer, b"application/epub+zip")?;
+
+    writer.start_file("META-INF/container.xml", options)?;
+    std::io::Write::write_all(&mut writer, container.as_bytes())?;
+
+    writer.start_file("OEBPS/content.opf", options)?;
+    std::io::Write::write_all(&mut writer, content.as_bytes())?;
+
+    writer.start_file("OEBPS/nav.xhtml", options)?;
+    std::io::Write::write_all(&mut writer, nav.as_bytes())?;
+
+    for (name, data) in files {
+        writer.start_file(name, options)?;
+        std::io::Write::write_all(&mut writer, &data)?;
+    }
+
+    Ok(writer.finish()?.into_inner())
+}
\ No newline at end of file
@@ -1,4 +1,56 @@
 pub mod entry;
+pub mod comic_info;
+pub mod naming;
+pub mod strip;
+pub mod eink;
+pub mod spread;
+pub mod export;
+
+use crate::api;
+use std::convert::TryFrom;
+
+fn make_entry(inode: &entry::Inode) -> std::io::Result<polyfuse::reply::ReplyEntry> {
+    let mut reply = polyfuse::reply::ReplyEntry::default();
+
+    match inode.get_attr() {
+        Some(attr) => {
+            reply.ino(attr.ino());
+            reply.attr(attr);
+            reply.ttl_attr(std::time::Duration::from_secs(1u64));
+            reply.ttl_entry(std::time::Duration::from_secs(1u64));
+
+            Ok(reply)
+        },
+        None => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+    }
+}
+
+// Accepts either a bare id ("12345") or a pasted MangaDex URL with slashes
+// replaced by the shell or file manager ("mangadex.org_title_12345_berserk").
+fn parse_manga_id(name: &std::ffi::OsStr) -> Option<u64> {
+    let name = name.to_str()?;
+    let mut tokens = name.split(|c: char| !c.is_ascii_alphanumeric()).filter(|token| !token.is_empty());
+
+    match name.parse::<u64>() {
+        Ok(id) => Some(id),
+        Err(_) => {
+            let mut first_id = None;
+
+            while let Some(token) = tokens.next() {
+                if token == "title" || token == "manga" {
+                    if let Some(id) = tokens.next().and_then(|token| token.parse::<u64>().ok()) {
+                        return Some(id);
+                    }
+                }
+                else if first_id.is_none() {
+                    first_id = token.parse::<u64>().ok();
+                }
+            }
+
+            first_id
+        }
+    }
+}
 
 pub struct MangaDexFS {
     context: std::sync::Arc<crate::Context>
@@ -11,7 +63,54 @@ impl MangaDexFS {
         }
     }
 
+    // Refetches follows and mdlist directories once their contents are older than the configured interval.
+    async fn refresh_listing(&self, ino: u64) -> std::io::Result<()> {
+        let (status, refreshed) = match self.context.entries.read().await.get(&ino) {
+            Some(entry::Inode(entry::Entry::Follows(_, refreshed), _)) => (None, *refreshed),
+            Some(entry::Inode(entry::Entry::MDList(status, _, refreshed), _)) => (Some(*status), *refreshed),
+            _ => return Ok(())
+        };
+
+        if !self.context.needs_refresh(refreshed) {
+            return Ok(());
+        }
+
+        let result = match status {
+            Some(status) => self.context.refresh_mdlist(ino, status).await,
+            None => self.context.refresh_follows(ino).await
+        };
+
+        match (result, refreshed) {
+            (Ok(()), _) => Ok(()),
+            (Err(error), Some(_)) => {
+                warn!("refreshing listing {} error: {:?}, keeping previous contents", ino, error);
+                Ok(())
+            },
+            (Err(error), None) => {
+                warn!("refreshing listing {} error: {:?}", ino, error);
+
+                match error {
+                    api::APIError::Request(_) => Err(std::io::Error::from_raw_os_error(libc::EIO)),
+                    api::APIError::NotLoggedIn => Err(std::io::Error::from_raw_os_error(libc::EACCES))
+                }
+            }
+        }
+    }
+
     async fn do_lookup(&self, op: &polyfuse::op::Lookup<'_>) -> std::io::Result<polyfuse::reply::ReplyEntry> {
+        self.refresh_listing(op.parent()).await?;
+
+        // Listings like follows/ hold their own not fetched chapter inode, its children are looked up in the fetched chapter.
+        let not_fetched = match self.context.entries.read().await.get(&op.parent()) {
+            Some(entry::Inode(entry::Entry::ChapterNotFetched(chapter_id), _)) => Some(*chapter_id),
+            _ => None
+        };
+
+        let parent = match not_fetched {
+            Some(chapter_id) => self.fetch_chapter_not_fetched(chapter_id).await?,
+            None => op.parent()
+        };
+
         let entries = self.context.entries.read().await;
 
         let make_result = |directory: &entry::Directory| -> std::io::Result<polyfuse::reply::ReplyEntry> {
@@ -19,22 +118,7 @@ impl MangaDexFS {
                 // If child direntry is found, find its ino in entries
                 Some(child_direntry) => match entries.get(&child_direntry.nodeid()) {
                     // If child inode is found
-                    Some(child_inode) => {
-                        let mut reply = polyfuse::reply::ReplyEntry::default();
-                        let attr = child_inode.get_attr();
-
-                        match attr {
-                            Some(attr) => {
-                                reply.ino(attr.ino());
-                                reply.attr(attr);
-                                reply.ttl_attr(std::time::Duration::from_secs(1u64));
-                                reply.ttl_entry(std::time::Duration::from_secs(1u64));
-
-                                Ok(reply)
-                            },
-                            None => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
-                        }
-                    },
+                    Some(child_inode) => make_entry(child_inode),
                     None => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
                 },
                 None => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
@@ -42,18 +126,241 @@ impl MangaDexFS {
         };
 
         // Find parent entry from op parent
-        match entries.get(&op.parent()) {
+        match entries.get(&parent) {
             Some(entry::Inode(entry::Entry::Root(directory), _)) => make_result(directory),
+            Some(entry::Inode(entry::Entry::Directory(directory), _)) => make_result(directory),
+            Some(entry::Inode(entry::Entry::Follows(directory, _), _)) => make_result(directory),
+            Some(entry::Inode(entry::Entry::MDList(_, directory, _), _)) => make_result(directory),
+            Some(entry::Inode(entry::Entry::ByIdManga, _)) => {
+                drop(entries);
+                self.lookup_by_id_manga(op).await
+            },
+            Some(entry::Inode(entry::Entry::ByIdChapter, _)) => {
+                drop(entries);
+                self.lookup_by_id_chapter(op).await
+            },
+            Some(entry::Inode(entry::Entry::Search(directory), _)) => match make_result(directory) {
+                Err(error) if error.raw_os_error() == Some(libc::ENOENT) && op.name().to_string_lossy().contains('=') => {
+                    drop(entries);
+                    self.lookup_search(op).await
+                },
+                result => result
+            },
+            Some(entry::Inode(entry::Entry::MangaNotFetched(manga_id_ref), _)) => {
+                let manga_id = *manga_id_ref;
+                drop(entries);
+
+                let manga_ino = self.fetch_manga_not_fetched(manga_id).await?;
+
+                let entries = self.context.entries.read().await;
+                match entries.get(&manga_ino) {
+                    Some(entry::Inode(entry::Entry::Manga(_, directory), _)) => match directory.entries().into_iter().find(|direntry| direntry.name() == op.name()) {
+                        Some(child_direntry) => match entries.get(&child_direntry.nodeid()) {
+                            Some(child_inode) => make_entry(child_inode),
+                            None => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+                        },
+                        None => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+                    },
+                    _ => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+                }
+            },
             Some(entry::Inode(entry::Entry::Manga(_, directory), _)) => make_result(directory),
-            Some(entry::Inode(entry::Entry::Chapter(_, directory), _)) => make_result(directory),
+            Some(entry::Inode(entry::Entry::Chapter(chapter_ref, directory), _)) => {
+                // Opening a page of another chapter means the reader has moved on.
+                if let Some(chapter) = chapter_ref.upgrade() {
+                    self.context.cancel_read_ahead(chapter.id).await;
+                }
+
+                match make_result(directory) {
+                    // Pages skipped by a cancelled read ahead are fetched when they're looked up.
+                    Err(error) if error.raw_os_error() == Some(libc::ENOENT) && directory.children.contains_key(std::path::Path::new(op.name())) => {
+                        let chapter_id = match chapter_ref.upgrade() {
+                            Some(chapter) => chapter.id,
+                            None => return Err(std::io::Error::from_raw_os_error(libc::EIO))
+                        };
+                        drop(entries);
+
+                        match self.context.get_or_fetch_missing_page(chapter_id, op.name()).await {
+                            Some(page_ino) => match self.context.entries.read().await.get(&page_ino) {
+                                Some(page_inode) => make_entry(page_inode),
+                                None => Err(std::io::Error::from_raw_os_error(libc::EIO))
+                            },
+                            None => Err(std::io::Error::from_raw_os_error(libc::EIO))
+                        }
+                    },
+                    result => result
+                }
+            },
             Some(entry::Inode(entry::Entry::ChapterNotFetched(_), _)) => Err(std::io::Error::from_raw_os_error(libc::EINVAL)),
+            Some(entry::Inode(entry::Entry::StripNotStitched(_), _)) | Some(entry::Inode(entry::Entry::SplitNotMade(_), _)) => {
+                drop(entries);
+
+                self.make_chapter_view(parent).await?;
+
+                let entries = self.context.entries.read().await;
+                match entries.get(&parent) {
+                    Some(entry::Inode(entry::Entry::Directory(directory), _)) => match directory.entries().into_iter().find(|direntry| direntry.name() == op.name()) {
+                        Some(child_direntry) => match entries.get(&child_direntry.nodeid()) {
+                            Some(child_inode) => make_entry(child_inode),
+                            None => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+                        },
+                        None => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+                    },
+                    _ => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+                }
+            },
             Some(entry::Inode(entry::Entry::Page(_), _)) => Err(std::io::Error::from_raw_os_error(libc::ENOTDIR)),
+            Some(entry::Inode(entry::Entry::EInkPage(_, _), _)) => Err(std::io::Error::from_raw_os_error(libc::ENOTDIR)),
+            Some(entry::Inode(entry::Entry::StripImage(_), _)) => Err(std::io::Error::from_raw_os_error(libc::ENOTDIR)),
+            Some(entry::Inode(entry::Entry::SpreadHalf(_), _)) => Err(std::io::Error::from_raw_os_error(libc::ENOTDIR)),
+            Some(entry::Inode(entry::Entry::Export(_, _, _), _)) => Err(std::io::Error::from_raw_os_error(libc::ENOTDIR)),
+            Some(entry::Inode(entry::Entry::AllPages(chapter_ids), _)) => {
+                let chapter_ids = chapter_ids.clone();
+                drop(entries);
+
+                let page_ino = match self.context.all_pages_lookup(&chapter_ids, &op.name().to_string_lossy()).await {
+                    Ok(Some(page_ino)) => page_ino,
+                    Ok(None) => return Err(std::io::Error::from_raw_os_error(libc::ENOENT)),
+                    Err(error) => {
+                        debug!("chapter fetching error: {}", error);
+                        return Err(std::io::Error::from_raw_os_error(libc::EIO));
+                    }
+                };
+
+                match self.context.entries.read().await.get(&page_ino) {
+                    Some(page_inode) => make_entry(page_inode),
+                    None => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+                }
+            },
             Some(entry::Inode(entry::Entry::Cover(_), _)) => Err(std::io::Error::from_raw_os_error(libc::ENOTDIR)),
             Some(entry::Inode(entry::Entry::External(_), _)) => Err(std::io::Error::from_raw_os_error(libc::ENOTDIR)),
+            Some(entry::Inode(entry::Entry::ComicInfo(_), _)) => Err(std::io::Error::from_raw_os_error(libc::ENOTDIR)),
+            Some(entry::Inode(entry::Entry::Symlink(_), _)) => Err(std::io::Error::from_raw_os_error(libc::ENOTDIR)),
             None => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
         }
     }
 
+    async fn lookup_by_id_manga(&self, op: &polyfuse::op::Lookup<'_>) -> std::io::Result<polyfuse::reply::ReplyEntry> {
+        let id = match op.name().to_str().and_then(|name| name.parse::<u64>().ok()) {
+            Some(id) => id,
+            None => return Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+        };
+
+        match self.context.get_or_fetch_manga(id, self.context.config.languages.clone()).await {
+            Ok(_) => match self.context.manga_ino(id).await {
+                Some(manga_ino) => match self.context.entries.read().await.get(&manga_ino) {
+                    Some(inode) => make_entry(inode),
+                    None => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+                },
+                None => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+            },
+            Err(error) => {
+                warn!("by-id manga {} fetching error: {}", id, error);
+                Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+            }
+        }
+    }
+
+    async fn lookup_by_id_chapter(&self, op: &polyfuse::op::Lookup<'_>) -> std::io::Result<polyfuse::reply::ReplyEntry> {
+        let id = match op.name().to_str().and_then(|name| name.parse::<u64>().ok()) {
+            Some(id) => id,
+            None => return Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+        };
+
+        match self.context.get_or_fetch_chapter(id).await {
+            Ok(_) => match self.context.chapter_ino(id).await {
+                Some(chapter_ino) => match self.context.entries.read().await.get(&chapter_ino) {
+                    Some(inode) => make_entry(inode),
+                    None => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+                },
+                None => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+            },
+            Err(error) => {
+                warn!("by-id chapter {} fetching error: {}", id, error);
+                Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+            }
+        }
+    }
+
+    async fn search_directory(&self, parent: u64, name: &std::ffi::OsStr) -> std::io::Result<polyfuse::reply::ReplyEntry> {
+        let params = match name.to_str().and_then(|query| api::SearchParams::try_from(query).ok()) {
+            Some(params) => params,
+            None => return Err(std::io::Error::from_raw_os_error(libc::EINVAL))
+        };
+
+        match self.context.make_search_directory(parent, name, &params).await {
+            Ok(directory_ino) => match self.context.entries.read().await.get(&directory_ino) {
+                Some(inode) => make_entry(inode),
+                None => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+            },
+            Err(error) => {
+                warn!("search directory {:?} error: {:?}", name, error);
+
+                match error {
+                    api::APIError::Request(_) => Err(std::io::Error::from_raw_os_error(libc::EIO)),
+                    api::APIError::NotLoggedIn => Err(std::io::Error::from_raw_os_error(libc::EACCES))
+                }
+            }
+        }
+    }
+
+    async fn lookup_search(&self, op: &polyfuse::op::Lookup<'_>) -> std::io::Result<polyfuse::reply::ReplyEntry> {
+        match self.search_directory(op.parent(), op.name()).await {
+            Err(error) if error.raw_os_error() == Some(libc::EINVAL) => Err(std::io::Error::from_raw_os_error(libc::ENOENT)),
+            result => result
+        }
+    }
+
+    async fn fetch_manga_not_fetched(&self, manga_id: u64) -> std::io::Result<u64> {
+        match self.context.get_or_fetch_manga(manga_id, self.context.config.languages.clone()).await {
+            Ok(_) => match self.context.manga_ino(manga_id).await {
+                Some(manga_ino) => Ok(manga_ino),
+                None => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+            },
+            Err(error) => {
+                debug!("manga fetching error: {}", error);
+                Err(std::io::Error::from_raw_os_error(libc::EIO))
+            }
+        }
+    }
+
+    // Returns the inode the chapter was fetched into.
+    async fn fetch_chapter_not_fetched(&self, chapter_id: u64) -> std::io::Result<u64> {
+        match self.context.get_or_fetch_chapter(chapter_id).await {
+            Ok(_) => match self.context.chapter_ino(chapter_id).await {
+                Some(chapter_ino) => Ok(chapter_ino),
+                None => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+            },
+            Err(error) => {
+                debug!("chapter fetching error: {}", error);
+                Err(std::io::Error::from_raw_os_error(libc::EIO))
+            }
+        }
+    }
+
+    // Builds the strip/ or split/ view of a chapter when it's first accessed.
+    async fn make_chapter_view(&self, ino: u64) -> std::io::Result<()> {
+        let view = match self.context.entries.read().await.get(&ino) {
+            Some(entry::Inode(entry::Entry::StripNotStitched(chapter_id), _)) => Some((*chapter_id, true)),
+            Some(entry::Inode(entry::Entry::SplitNotMade(chapter_id), _)) => Some((*chapter_id, false)),
+            _ => None
+        };
+
+        let result = match view {
+            Some((chapter_id, true)) => self.context.get_or_stitch_strip(ino, chapter_id).await,
+            Some((chapter_id, false)) => self.context.get_or_split_spreads(ino, chapter_id).await,
+            None => Ok(())
+        };
+
+        match result {
+            Ok(()) => Ok(()),
+            Err(error) => {
+                warn!("making chapter view {} error: {}", ino, error);
+                Err(std::io::Error::from_raw_os_error(libc::EIO))
+            }
+        }
+    }
+
     async fn do_getattr(&self, op: &polyfuse::op::Getattr<'_>) -> std::io::Result<polyfuse::reply::ReplyAttr> {
         match self.context.entries.read().await.get(&op.ino()).and_then(|inode| inode.get_attr()) {
             Some(file_attr) => Ok({                
@@ -70,7 +377,68 @@ impl MangaDexFS {
 
         match read_lock.get(&op.ino()) {
             Some(entry::Inode(entry::Entry::Page(page_ref), _)) => match page_ref.upgrade() {
-                Some(page) => Ok(page.0[op.offset() as usize..std::cmp::min(op.offset() as usize + op.size() as usize, page.0.len())].into()),
+                Some(page) => {
+                    drop(read_lock);
+
+                    if self.context.config.auto_mark_read {
+                        self.context.page_read(page.chapter_id, page.index).await;
+                    }
+
+                    // Once per page, not for every chunk of it.
+                    if self.context.config.read_ahead.is_some() && op.offset() == 0u64 {
+                        tokio::spawn(self.context.clone().read_ahead(page.chapter_id, page.index));
+                    }
+
+                    self.context.prioritise_page(&page.url);
+
+                    let page = match self.context.refetch_if_failed(page).await {
+                        Some(page) => page,
+                        None => return Err(std::io::Error::from_raw_os_error(libc::EIO))
+                    };
+
+                    match page.data.read(op.offset() as usize, op.size() as usize).await {
+                        Some(data) => Ok(data),
+                        None => Err(std::io::Error::from_raw_os_error(libc::EIO))
+                    }
+                },
+                None => Err(std::io::Error::from_raw_os_error(libc::EIO))
+            },
+            Some(entry::Inode(entry::Entry::EInkPage(page_ref, _), _)) => match page_ref.upgrade() {
+                Some(page) => {
+                    drop(read_lock);
+
+                    if self.context.config.auto_mark_read {
+                        self.context.page_read(page.chapter_id, page.index).await;
+                    }
+
+                    // Once per page, not for every chunk of it.
+                    if self.context.config.read_ahead.is_some() && op.offset() == 0u64 {
+                        tokio::spawn(self.context.clone().read_ahead(page.chapter_id, page.index));
+                    }
+
+                    self.context.prioritise_page(&page.url);
+
+                    match self.context.get_or_transcode_page(op.ino()).await {
+                        Some(transcoded) => Ok(transcoded.0[std::cmp::min(op.offset() as usize, transcoded.0.len())..std::cmp::min(op.offset() as usize + op.size() as usize, transcoded.0.len())].into()),
+                        None => Err(std::io::Error::from_raw_os_error(libc::EIO))
+                    }
+                },
+                None => Err(std::io::Error::from_raw_os_error(libc::EIO))
+            },
+            Some(entry::Inode(entry::Entry::Export(_, _, _), _)) => {
+                drop(read_lock);
+
+                match self.context.get_or_build_export(op.ino()).await {
+                    Some(export) => Ok(export.0[std::cmp::min(op.offset() as usize, export.0.len())..std::cmp::min(op.offset() as usize + op.size() as usize, export.0.len())].into()),
+                    None => Err(std::io::Error::from_raw_os_error(libc::EIO))
+                }
+            },
+            Some(entry::Inode(entry::Entry::SpreadHalf(spread_half_ref), _)) => match spread_half_ref.upgrade() {
+                Some(spread_half) => Ok(spread_half.data[std::cmp::min(op.offset() as usize, spread_half.data.len())..std::cmp::min(op.offset() as usize + op.size() as usize, spread_half.data.len())].into()),
+                None => Err(std::io::Error::from_raw_os_error(libc::EIO))
+            },
+            Some(entry::Inode(entry::Entry::StripImage(strip_image_ref), _)) => match strip_image_ref.upgrade() {
+                Some(strip_image) => Ok(strip_image.data[std::cmp::min(op.offset() as usize, strip_image.data.len())..std::cmp::min(op.offset() as usize + op.size() as usize, strip_image.data.len())].into()),
                 None => Err(std::io::Error::from_raw_os_error(libc::EIO))
             },
             Some(entry::Inode(entry::Entry::Cover(cover_ref), _)) => match cover_ref.upgrade() {
@@ -78,11 +446,158 @@ impl MangaDexFS {
                 None => Err(std::io::Error::from_raw_os_error(libc::EIO))
             },
             Some(entry::Inode(entry::Entry::External(bytes), _)) => Ok(bytes[op.offset() as usize..std::cmp::min(op.offset() as usize + op.size() as usize, bytes.len())].into()),
+            Some(entry::Inode(entry::Entry::ComicInfo(bytes), _)) => Ok(bytes[std::cmp::min(op.offset() as usize, bytes.len())..std::cmp::min(op.offset() as usize + op.size() as usize, bytes.len())].into()),
             Some(_) => Err(std::io::Error::from_raw_os_error(libc::EINVAL)),
             None => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
         }
     }
 
+    async fn do_mkdir(&self, op: &polyfuse::op::Mkdir<'_>) -> std::io::Result<polyfuse::reply::ReplyEntry> {
+        match self.context.entries.read().await.get(&op.parent()) {
+            Some(entry::Inode(entry::Entry::Root(_), _)) => (),
+            Some(entry::Inode(entry::Entry::Search(_), _)) => return self.search_directory(op.parent(), op.name()).await,
+            Some(_) => return Err(std::io::Error::from_raw_os_error(libc::EPERM)),
+            None => return Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+        };
+
+        let id = match parse_manga_id(op.name()) {
+            Some(id) => id,
+            None => return Err(std::io::Error::from_raw_os_error(libc::EINVAL))
+        };
+
+        match self.context.get_or_fetch_manga(id, self.context.config.languages.clone()).await {
+            Ok(_) => match self.context.manga_ino(id).await {
+                Some(manga_ino) => match self.context.entries.read().await.get(&manga_ino) {
+                    Some(inode) => make_entry(inode),
+                    None => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+                },
+                None => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+            },
+            Err(error) => {
+                warn!("mkdir manga {} error: {}", id, error);
+                Err(std::io::Error::from_raw_os_error(libc::EIO))
+            }
+        }
+    }
+
+    async fn do_rmdir(&self, op: &polyfuse::op::Rmdir<'_>) -> std::io::Result<()> {
+        let read_lock = self.context.entries.read().await;
+
+        let manga_id = match read_lock.get(&op.parent()) {
+            Some(entry::Inode(entry::Entry::Root(directory), _)) => match directory.children.get(std::path::Path::new(op.name())) {
+                Some((child_ino, _)) => match read_lock.get(child_ino) {
+                    Some(entry::Inode(entry::Entry::Manga(manga_ref, _), _)) => manga_ref.upgrade().map(|manga| manga.id),
+                    Some(_) => return Err(std::io::Error::from_raw_os_error(libc::EPERM)),
+                    None => None
+                },
+                None => None
+            },
+            Some(entry::Inode(entry::Entry::Search(_), _)) => {
+                drop(read_lock);
+
+                return match self.context.remove_search_directory(op.parent(), op.name()).await {
+                    true => Ok(()),
+                    false => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+                };
+            },
+            Some(_) => return Err(std::io::Error::from_raw_os_error(libc::EPERM)),
+            None => None
+        };
+
+        drop(read_lock);
+
+        match manga_id {
+            Some(manga_id) if self.context.is_pinned(manga_id).await => {
+                info!("not removing pinned manga {}, unpin it first", manga_id);
+                Err(std::io::Error::from_raw_os_error(libc::EBUSY))
+            },
+            Some(manga_id) => match self.context.remove_manga(manga_id).await {
+                Some(manga) => {
+                    info!("removed manga {}: {}", manga.id, manga.to_string());
+                    Ok(())
+                },
+                None => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+            },
+            None => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+        }
+    }
+
+    async fn xattrs(&self, ino: u64) -> std::io::Result<Vec<(&'static str, String)>> {
+        let read_lock = self.context.entries.read().await;
+
+        match read_lock.get(&ino) {
+            Some(entry::Inode(entry::Entry::Manga(manga_ref, _), _)) => match manga_ref.upgrade() {
+                Some(manga) => Ok(vec![
+                    ("user.mangadex.manga_id", manga.id.to_string()),
+                    ("user.mangadex.source_url", format!("https://mangadex.org/title/{}", manga.id))
+                ]),
+                None => Err(std::io::Error::from_raw_os_error(libc::EIO))
+            },
+            Some(entry::Inode(entry::Entry::Chapter(chapter_ref, _), _)) => match chapter_ref.upgrade() {
+                Some(chapter) => Ok(vec![
+                    ("user.mangadex.manga_id", chapter.manga_id.to_string()),
+                    ("user.mangadex.chapter_id", chapter.id.to_string()),
+                    ("user.mangadex.lang_code", chapter.lang_code.clone()),
+                    ("user.mangadex.group_names", chapter.groups.iter().map(|(_, name)| name.as_str()).collect::<Vec<_>>().join(", ")),
+                    ("user.mangadex.source_url", format!("https://mangadex.org/chapter/{}", chapter.id)),
+                    ("user.mangadex.timestamp", chapter.timestamp.to_string())
+                ]),
+                None => Err(std::io::Error::from_raw_os_error(libc::EIO))
+            },
+            Some(entry::Inode(entry::Entry::MangaNotFetched(manga_id), _)) => Ok(vec![
+                ("user.mangadex.manga_id", manga_id.to_string()),
+                ("user.mangadex.source_url", format!("https://mangadex.org/title/{}", manga_id))
+            ]),
+            Some(entry::Inode(entry::Entry::ChapterNotFetched(chapter_id), _)) => Ok(vec![
+                ("user.mangadex.chapter_id", chapter_id.to_string()),
+                ("user.mangadex.source_url", format!("https://mangadex.org/chapter/{}", chapter_id))
+            ]),
+            Some(entry::Inode(entry::Entry::Page(page_ref), _)) | Some(entry::Inode(entry::Entry::EInkPage(page_ref, _), _)) => match page_ref.upgrade() {
+                Some(page) => {
+                    drop(read_lock);
+
+                    let mut xattrs = vec![];
+
+                    if let Some(chapter) = self.context.chapters.read().await.get(&page.chapter_id) {
+                        xattrs.push(("user.mangadex.manga_id", chapter.manga_id.to_string()));
+                        xattrs.push(("user.mangadex.lang_code", chapter.lang_code.clone()));
+                        xattrs.push(("user.mangadex.group_names", chapter.groups.iter().map(|(_, name)| name.as_str()).collect::<Vec<_>>().join(", ")));
+                        xattrs.push(("user.mangadex.timestamp", chapter.timestamp.to_string()));
+                    }
+
+                    xattrs.push(("user.mangadex.chapter_id", page.chapter_id.to_string()));
+                    xattrs.push(("user.mangadex.page_index", page.index.to_string()));
+
+                    if let Some((width, height)) = page.dimensions() {
+                        xattrs.push(("user.mangadex.width", width.to_string()));
+                        xattrs.push(("user.mangadex.height", height.to_string()));
+                        xattrs.push(("user.mangadex.double_page", page.is_spread().to_string()));
+                    }
+                    xattrs.push(("user.mangadex.source_url", page.url.to_string()));
+
+                    Ok(xattrs)
+                },
+                None => Err(std::io::Error::from_raw_os_error(libc::EIO))
+            },
+            Some(_) => Ok(vec![]),
+            None => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+        }
+    }
+
+    async fn do_getxattr(&self, op: &polyfuse::op::Getxattr<'_>) -> std::io::Result<Vec<u8>> {
+        match self.xattrs(op.ino()).await?.into_iter().find(|(name, _)| std::ffi::OsStr::new(name) == op.name()) {
+            Some((_, value)) => Ok(value.into_bytes()),
+            None => Err(std::io::Error::from_raw_os_error(libc::ENODATA))
+        }
+    }
+
+    async fn do_listxattr(&self, op: &polyfuse::op::Listxattr<'_>) -> std::io::Result<Vec<u8>> {
+        Ok(self.xattrs(op.ino()).await?
+            .into_iter()
+            .flat_map(|(name, _)| name.bytes().chain(std::iter::once(0u8)))
+            .collect())
+    }
+
     async fn do_readdir(&self, op: &polyfuse::op::Readdir<'_>) -> std::io::Result<Vec<u8>> {
         let make_reply = |directory: &entry::Directory| -> Vec<u8> {
             let entries = {
@@ -117,10 +632,53 @@ impl MangaDexFS {
             entries_reply
         };
 
+        if op.offset() == 0u64 {
+            self.refresh_listing(op.ino()).await?;
+        }
+
         let read_lock = self.context.entries.read().await;
 
         match read_lock.get(&op.ino()) {
             Some(entry::Inode(entry::Entry::Root(directory), _)) => Ok(make_reply(directory)),
+            Some(entry::Inode(entry::Entry::Directory(directory), _)) => Ok(make_reply(directory)),
+            Some(entry::Inode(entry::Entry::Follows(directory, _), _)) => Ok(make_reply(directory)),
+            Some(entry::Inode(entry::Entry::MDList(_, directory, _), _)) => Ok(make_reply(directory)),
+            Some(entry::Inode(entry::Entry::Search(directory), _)) => Ok(make_reply(directory)),
+            Some(entry::Inode(entry::Entry::MangaNotFetched(manga_id_ref), _)) => {
+                let manga_id = *manga_id_ref;
+                drop(read_lock);
+
+                let manga_ino = self.fetch_manga_not_fetched(manga_id).await?;
+
+                match self.context.entries.read().await.get(&manga_ino) {
+                    Some(entry::Inode(entry::Entry::Manga(_, directory), _)) => Ok(make_reply(directory)),
+                    _ => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+                }
+            },
+            Some(entry::Inode(entry::Entry::ByIdManga, _)) => {
+                drop(read_lock);
+
+                let mut directory = entry::Directory::new(crate::context::BY_ID_INO);
+                for id in self.context.manga.read().await.keys() {
+                    if let Some(manga_ino) = self.context.manga_ino(*id).await {
+                        directory.children.insert(id.to_string().into(), (manga_ino, false));
+                    }
+                }
+
+                Ok(make_reply(&directory))
+            },
+            Some(entry::Inode(entry::Entry::ByIdChapter, _)) => {
+                drop(read_lock);
+
+                let mut directory = entry::Directory::new(crate::context::BY_ID_INO);
+                for id in self.context.chapters.read().await.keys() {
+                    if let Some(chapter_ino) = self.context.chapter_ino(*id).await {
+                        directory.children.insert(id.to_string().into(), (chapter_ino, false));
+                    }
+                }
+
+                Ok(make_reply(&directory))
+            },
             Some(entry::Inode(entry::Entry::Manga(_, directory), _)) => Ok(make_reply(directory)),
             Some(entry::Inode(entry::Entry::Chapter(_, directory), _)) => Ok(make_reply(directory)),
             Some(entry::Inode(entry::Entry::ChapterNotFetched(chapter_id_ref), _)) => {
@@ -131,22 +689,134 @@ impl MangaDexFS {
 
                 debug!("chapter not fetched: {}", chapter_id);
 
-                match self.context.get_or_fetch_chapter(chapter_id).await {
-                    Ok(_) => {
-                        match self.context.entries.read().await.get(&op.ino()) {
-                            Some(entry::Inode(entry::Entry::Chapter(_, directory), _)) => Ok(make_reply(directory)),
-                            _ => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
-                        }
-                    },
-                    Err(error) => {
-                        debug!("chapter fetching error: {}", error);
-                        Err(std::io::Error::from_raw_os_error(libc::EIO))
-                    }
+                // Listings like follows/ hold their own not fetched inode, the fetched chapter lives at its canonical one.
+                let chapter_ino = self.fetch_chapter_not_fetched(chapter_id).await?;
+
+                match self.context.entries.read().await.get(&chapter_ino) {
+                    Some(entry::Inode(entry::Entry::Chapter(_, directory), _)) => Ok(make_reply(directory)),
+                    _ => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+                }
+            },
+            Some(entry::Inode(entry::Entry::StripNotStitched(_), _)) | Some(entry::Inode(entry::Entry::SplitNotMade(_), _)) => {
+                drop(read_lock);
+
+                self.make_chapter_view(op.ino()).await?;
+
+                match self.context.entries.read().await.get(&op.ino()) {
+                    Some(entry::Inode(entry::Entry::Directory(directory), _)) => Ok(make_reply(directory)),
+                    _ => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
                 }
             },
             Some(entry::Inode(entry::Entry::Page(_), _)) => Err(std::io::Error::from_raw_os_error(libc::ENOTDIR)),
+            Some(entry::Inode(entry::Entry::EInkPage(_, _), _)) => Err(std::io::Error::from_raw_os_error(libc::ENOTDIR)),
+            Some(entry::Inode(entry::Entry::StripImage(_), _)) => Err(std::io::Error::from_raw_os_error(libc::ENOTDIR)),
+            Some(entry::Inode(entry::Entry::SpreadHalf(_), _)) => Err(std::io::Error::from_raw_os_error(libc::ENOTDIR)),
+            Some(entry::Inode(entry::Entry::Export(_, _, _), _)) => Err(std::io::Error::from_raw_os_error(libc::ENOTDIR)),
+            Some(entry::Inode(entry::Entry::AllPages(chapter_ids), _)) => {
+                let chapter_ids = chapter_ids.clone();
+                drop(read_lock);
+
+                Ok(make_reply(&self.context.make_all_pages_directory(op.ino(), &chapter_ids).await))
+            },
             Some(entry::Inode(entry::Entry::Cover(_), _)) => Err(std::io::Error::from_raw_os_error(libc::ENOTDIR)),
             Some(entry::Inode(entry::Entry::External(_), _)) => Err(std::io::Error::from_raw_os_error(libc::ENOTDIR)),
+            Some(entry::Inode(entry::Entry::ComicInfo(_), _)) => Err(std::io::Error::from_raw_os_error(libc::ENOTDIR)),
+            Some(entry::Inode(entry::Entry::Symlink(_), _)) => Err(std::io::Error::from_raw_os_error(libc::ENOTDIR)),
+            None => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+        }
+    }
+
+    async fn file_mode(&self, ino: u64) -> std::io::Result<u32> {
+        match self.context.entries.read().await.get(&ino) {
+            Some(inode) => match inode.get_attr() {
+                Some(attr) => Ok(attr.mode()),
+                None => Err(std::io::Error::from_raw_os_error(libc::EIO))
+            },
+            None => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
+        }
+    }
+
+    async fn do_open(&self, op: &polyfuse::op::Open<'_>) -> std::io::Result<polyfuse::reply::ReplyOpen> {
+        let mode = self.file_mode(op.ino()).await?;
+
+        if mode & libc::S_IFMT == libc::S_IFDIR {
+            Err(std::io::Error::from_raw_os_error(libc::EISDIR))
+        }
+        else if op.flags() as i32 & libc::O_ACCMODE != libc::O_RDONLY || op.flags() as i32 & libc::O_TRUNC != 0i32 {
+            Err(std::io::Error::from_raw_os_error(libc::EROFS))
+        }
+        else {
+            // Transcoding and exporting up front lets reads see the final size.
+            let (not_transcoded, not_exported) = match self.context.entries.read().await.get(&op.ino()) {
+                Some(entry::Inode(entry::Entry::EInkPage(_, None), _)) => (true, false),
+                Some(entry::Inode(entry::Entry::Export(_, _, None), _)) => (false, true),
+                _ => (false, false)
+            };
+
+            if not_transcoded {
+                self.context.get_or_transcode_page(op.ino()).await;
+            }
+
+            if not_exported && self.context.get_or_build_export(op.ino()).await.is_none() {
+                return Err(std::io::Error::from_raw_os_error(libc::EIO));
+            }
+
+            let mut reply = polyfuse::reply::ReplyOpen::new(0u64);
+            reply.keep_cache(true);
+            Ok(reply)
+        }
+    }
+
+    async fn do_opendir(&self, op: &polyfuse::op::Opendir<'_>) -> std::io::Result<polyfuse::reply::ReplyOpen> {
+        match self.file_mode(op.ino()).await? & libc::S_IFMT {
+            libc::S_IFDIR => Ok(polyfuse::reply::ReplyOpen::new(0u64)),
+            _ => Err(std::io::Error::from_raw_os_error(libc::ENOTDIR))
+        }
+    }
+
+    async fn do_access(&self, op: &polyfuse::op::Access<'_>) -> std::io::Result<()> {
+        let mode = self.file_mode(op.ino()).await?;
+        let mask = op.mask() as i32;
+
+        // Only the directories taking mkdir and rmdir (the root and search/) have write permission.
+        if mask & libc::W_OK != 0i32 && mode & 0o222 == 0u32 {
+            Err(std::io::Error::from_raw_os_error(libc::EROFS))
+        }
+        else if mask & libc::X_OK != 0i32 && mode & libc::S_IFMT != libc::S_IFDIR {
+            Err(std::io::Error::from_raw_os_error(libc::EACCES))
+        }
+        else {
+            Ok(())
+        }
+    }
+
+    // Reports the memory held by fetched pages and covers, and the images made from them, as the used space of a full filesystem.
+    async fn do_statfs(&self) -> std::io::Result<polyfuse::reply::ReplyStatfs> {
+        let pages_size = self.context.pages.read().await.values().map(|page| page.data.size()).sum::<u64>();
+        let covers_size = self.context.covers.read().await.values().map(|cover| cover.0.len() as u64).sum::<u64>();
+        let strips_size = self.context.strips.read().await.values().flat_map(|strip_images| strip_images.iter()).map(|strip_image| strip_image.data.len() as u64).sum::<u64>();
+        let transcoded_size = self.context.transcoded.read().await.values().map(|transcoded| transcoded.0.len() as u64).sum::<u64>();
+        let spreads_size = self.context.spreads.read().await.values().flat_map(|spread_halves| spread_halves.iter()).map(|spread_half| spread_half.data.len() as u64).sum::<u64>();
+        let exports_size = self.context.exports.read().await.values().map(|export| export.0.len() as u64).sum::<u64>();
+        let files = self.context.entries.read().await.len() as u64;
+
+        let mut st = polyfuse::StatFs::default();
+        st.set_bsize(4096u32);
+        st.set_frsize(4096u32);
+        st.set_blocks((pages_size + covers_size + strips_size + transcoded_size + spreads_size + exports_size).div_ceil(4096u64));
+        st.set_bfree(0u64);
+        st.set_bavail(0u64);
+        st.set_files(files);
+        st.set_ffree(0u64);
+        st.set_namelen(255u32);
+
+        Ok(polyfuse::reply::ReplyStatfs::new(st))
+    }
+
+    async fn do_readlink(&self, op: &polyfuse::op::Readlink<'_>) -> std::io::Result<Vec<u8>> {
+        match self.context.entries.read().await.get(&op.ino()) {
+            Some(entry::Inode(entry::Entry::Symlink(target), _)) => Ok(std::os::unix::ffi::OsStrExt::as_bytes(target.as_os_str()).to_vec()),
+            Some(_) => Err(std::io::Error::from_raw_os_error(libc::EINVAL)),
             None => Err(std::io::Error::from_raw_os_error(libc::ENOENT))
         }
     }
@@ -178,12 +848,67 @@ impl polyfuse::Filesystem for MangaDexFS {
             };
         }
 
+        macro_rules! try_reply_xattr {
+            ($e:expr, $size:expr) => {
+                match ($e).await {
+                    Ok(value) => match $size as usize {
+                        0usize => cx.reply(polyfuse::reply::ReplyXattr::new(value.len() as u32)).await,
+                        size if size < value.len() => cx.reply_err(libc::ERANGE).await,
+                        _ => cx.reply(value).await
+                    },
+                    Err(err) => {
+                        let errno = err.raw_os_error().unwrap_or(libc::EIO);
+                        cx.reply_err(errno).await
+                    }
+                }
+            };
+        }
+
         match op {
             polyfuse::Operation::Lookup(op) => try_reply!(self.do_lookup(&op)),
             polyfuse::Operation::Getattr(op) => try_reply!(self.do_getattr(&op)),
             polyfuse::Operation::Read(op) => try_reply!(self.do_read(&op)),
             polyfuse::Operation::Readdir(op) => try_reply!(self.do_readdir(&op)),
-            _ => Ok(()),
+            polyfuse::Operation::Readlink(op) => try_reply!(self.do_readlink(&op)),
+            polyfuse::Operation::Mkdir(op) => {
+                let result = try_reply!(self.do_mkdir(&op));
+
+                // The kernel keeps the typed name as a dentry, drop it so only the canonical entry remains.
+                if op.parent() == 1u64 {
+                    self.context.invalidate_entry(op.parent(), op.name()).await;
+                }
+
+                result
+            },
+            polyfuse::Operation::Rmdir(op) => try_reply!(self.do_rmdir(&op)),
+            polyfuse::Operation::Getxattr(op) => try_reply_xattr!(self.do_getxattr(&op), op.size()),
+            polyfuse::Operation::Listxattr(op) => try_reply_xattr!(self.do_listxattr(&op), op.size()),
+            polyfuse::Operation::Open(op) => try_reply!(self.do_open(&op)),
+            polyfuse::Operation::Opendir(op) => try_reply!(self.do_opendir(&op)),
+            polyfuse::Operation::Access(op) => try_reply!(self.do_access(&op)),
+            polyfuse::Operation::Statfs(_) => try_reply!(self.do_statfs()),
+            polyfuse::Operation::Release(_)
+            | polyfuse::Operation::Releasedir(_)
+            | polyfuse::Operation::Flush(_)
+            | polyfuse::Operation::Fsync(_)
+            | polyfuse::Operation::Fsyncdir(_) => cx.reply(()).await,
+            polyfuse::Operation::Setattr(_)
+            | polyfuse::Operation::Symlink(_)
+            | polyfuse::Operation::Mknod(_)
+            | polyfuse::Operation::Unlink(_)
+            | polyfuse::Operation::Rename(_)
+            | polyfuse::Operation::Link(_)
+            | polyfuse::Operation::Write(_)
+            | polyfuse::Operation::Setxattr(_)
+            | polyfuse::Operation::Removexattr(_)
+            | polyfuse::Operation::Create(_)
+            | polyfuse::Operation::Fallocate(_)
+            | polyfuse::Operation::CopyFileRange(_) => cx.reply_err(libc::EROFS).await,
+            // Forget, Interrupt and NotifyReply expect no reply.
+            polyfuse::Operation::Forget(_)
+            | polyfuse::Operation::Interrupt(_)
+            | polyfuse::Operation::NotifyReply(_) => Ok(()),
+            _ => cx.reply_err(libc::ENOSYS).await,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,183 @@
+use crate::api;
+
+pub struct ChapterFields<'a> {
+    pub id: u64,
+    pub volume: &'a str,
+    pub chapter: &'a str,
+    pub title: &'a str,
+    pub lang_code: &'a str,
+    pub groups: &'a [(u64, String)]
+}
+
+// Pads the integer part of a number ("5.5" with width 3 is "005.5"), anything else is kept as is.
+fn pad(value: &str, width: usize) -> String {
+    let (integer, rest) = match value.find('.') {
+        Some(index) => value.split_at(index),
+        None => (value, "")
+    };
+
+    if !integer.is_empty() && integer.chars().all(|c| c.is_ascii_digit()) {
+        format!("{:0>width$}{}", integer, rest, width = width)
+    }
+    else {
+        value.to_owned()
+    }
+}
+
+fn placeholder(name: &str, value: &dyn Fn(&str) -> Option<String>) -> Option<String> {
+    let mut split = name.splitn(2, ':');
+    let key = split.next().unwrap_or_default();
+
+    value(key).map(|text| match split.next().and_then(|width| width.parse::<usize>().ok()) {
+        Some(width) => pad(&text, width),
+        None => text
+    })
+}
+
+// Replaces "{key}" and "{key:width}" placeholders. A section enclosed in "<" and ">" is left out
+// entirely when any of its placeholders is empty. Unknown placeholders are kept verbatim.
+pub fn render(template: &str, value: &dyn Fn(&str) -> Option<String>) -> String {
+    let mut output = String::new();
+    let mut section: Option<(String, bool)> = None;
+    let mut chars = template.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '<' if section.is_none() => section = Some((String::new(), true)),
+            '>' if section.is_some() => {
+                if let Some((text, true)) = section.take() {
+                    output.push_str(&text);
+                }
+            },
+            '{' => {
+                let name = chars.by_ref().take_while(|&c| c != '}').collect::<String>();
+
+                let text = match placeholder(&name, value) {
+                    Some(text) => text,
+                    None => format!("{{{}}}", name)
+                };
+
+                match &mut section {
+                    Some((section_text, keep)) => {
+                        *keep = *keep && !text.is_empty();
+                        section_text.push_str(&text);
+                    },
+                    None => output.push_str(&text)
+                }
+            },
+            c => match &mut section {
+                Some((section_text, _)) => section_text.push(c),
+                None => output.push(c)
+            }
+        }
+    }
+
+    if let Some((text, true)) = section {
+        output.push_str(&text);
+    }
+
+    output
+}
+
+pub fn manga_name(template: Option<&str>, id: u64, title: &str) -> String {
+    let name = match template {
+        Some(template) => render(template, &|key| match key {
+            "title" => Some(title.to_owned()),
+            "id" => Some(id.to_string()),
+            _ => None
+        }),
+        None => format!("{} [{}]", title, id)
+    };
+
+    sanitize(name, id)
+}
+
+pub fn chapter_name(template: Option<&str>, show_groups: bool, manga_title: &str, fields: &ChapterFields) -> String {
+    let group = fields.groups.iter().map(|(_, name)| name.as_str()).collect::<Vec<_>>().join(", ");
+
+    let name = match template {
+        Some(template) => render(template, &|key| match key {
+            "title" => Some(manga_title.to_owned()),
+            "id" => Some(fields.id.to_string()),
+            "volume" => Some(fields.volume.to_owned()),
+            "chapter" => Some(fields.chapter.to_owned()),
+            "chapter_title" => Some(fields.title.to_owned()),
+            "lang" => Some(api::iso_639_1(fields.lang_code).to_owned()),
+            "group" => Some(group.clone()),
+            _ => None
+        }),
+        None => {
+            let groups = match show_groups && !group.is_empty() {
+                true => format!(" ({})", group),
+                false => String::default()
+            };
+
+            if fields.title == "Oneshot" {
+                format!("Oneshot{} [{}]", groups, fields.id)
+            }
+            else {
+                match (fields.title.is_empty(), fields.volume.is_empty()) {
+                    (true, true) => format!("Ch. {}{} [{}]", fields.chapter, groups, fields.id),
+                    (true, false) => format!("Vol. {} Ch. {}{} [{}]", fields.volume, fields.chapter, groups, fields.id),
+                    (false, true) => format!("Ch. {} - {}{} [{}]", fields.chapter, fields.title, groups, fields.id),
+                    _ => format!("Vol. {} Ch. {} - {}{} [{}]", fields.volume, fields.chapter, fields.title, groups, fields.id)
+                }
+            }
+        }
+    };
+
+    sanitize(name, fields.id)
+}
+
+// Names in the all/ directory, "c0001-p001.png" being the first page of the first chapter.
+pub fn all_pages_name(chapter: usize, page: usize, extension: &str) -> String {
+    format!("c{:04}-p{:03}.{}", chapter, page, extension)
+}
+
+// Parses an all/ name back into its chapter and page numbers, whatever its extension.
+pub fn parse_all_pages_name(name: &str) -> Option<(usize, usize)> {
+    let (stem, _) = name.split_once('.')?;
+    let mut parts = stem.strip_prefix('c')?.splitn(2, "-p");
+
+    let chapter = parts.next()?.parse::<usize>().ok()?;
+    let page = parts.next()?.parse::<usize>().ok()?;
+
+    Some((chapter, page))
+}
+
+fn sanitize(name: String, id: u64) -> String {
+    let name = sanitize_filename::sanitize(name.trim());
+
+    match name.is_empty() {
+        true => id.to_string(),
+        false => name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_pages_names_round_trip() {
+        assert_eq!(all_pages_name(1usize, 2usize, "png"), "c0001-p002.png");
+        assert_eq!(parse_all_pages_name(&all_pages_name(12usize, 345usize, "jpg")), Some((12usize, 345usize)));
+    }
+
+    #[test]
+    fn all_pages_names_take_any_extension() {
+        assert_eq!(parse_all_pages_name("c0003-p001.jpg"), Some((3usize, 1usize)));
+        assert_eq!(parse_all_pages_name("c0003-p001.png"), Some((3usize, 1usize)));
+        assert_eq!(parse_all_pages_name("c10000-p1000.gif"), Some((10000usize, 1000usize)));
+    }
+
+    #[test]
+    fn all_pages_names_need_every_part() {
+        assert_eq!(parse_all_pages_name("c0001-p001"), None);
+        assert_eq!(parse_all_pages_name("c0001.png"), None);
+        assert_eq!(parse_all_pages_name("0001-p001.png"), None);
+        assert_eq!(parse_all_pages_name("c0001-p.png"), None);
+        assert_eq!(parse_all_pages_name("cxx-p001.png"), None);
+        assert_eq!(parse_all_pages_name(""), None);
+    }
+}
\ No newline at end of file
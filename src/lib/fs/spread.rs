@@ -0,0 +1,30 @@
+fn encode(half: &image::RgbImage) -> Option<Vec<u8>> {
+    let mut data = vec![];
+
+    match image::codecs::jpeg::JpegEncoder::new_with_quality(&mut data, 90u8).encode(half, half.width(), half.height(), image::ColorType::Rgb8) {
+        Ok(()) => Some(data),
+        Err(error) => {
+            warn!("encoding spread half error: {}", error);
+            None
+        }
+    }
+}
+
+// Splits a double page spread down the middle. The right half comes first, as it is read first in manga.
+pub fn split(data: &[u8]) -> Option<(Vec<u8>, Vec<u8>)> {
+    let spread = match image::load_from_memory(data) {
+        Ok(spread) => spread.to_rgb8(),
+        Err(error) => {
+            warn!("decoding spread error: {}", error);
+            return None;
+        }
+    };
+
+    let (width, height) = spread.dimensions();
+    let half_width = width / 2u32;
+
+    let left = image::imageops::crop_imm(&spread, 0u32, 0u32, half_width, height).to_image();
+    let right = image::imageops::crop_imm(&spread, half_width, 0u32, width - half_width, height).to_image();
+
+    Some((encode(&right)?, encode(&left)?))
+}
\ No newline at end of file
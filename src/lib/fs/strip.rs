@@ -0,0 +1,57 @@
+// Stitches the slices of a long strip chapter into images of at most `max_height` pixels.
+// Slices are kept whole, so a slice taller than `max_height` becomes an image on its own,
+// and narrower slices are centered on a white background. Undecodable slices are skipped.
+pub fn stitch(pages: &[&[u8]], max_height: u32) -> Vec<Vec<u8>> {
+    let slices = pages
+        .iter()
+        .filter_map(|data| match image::load_from_memory(data) {
+            Ok(slice) => Some(slice.to_rgb8()),
+            Err(error) => {
+                warn!("decoding long strip slice error: {}", error);
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut groups: Vec<Vec<&image::RgbImage>> = vec![];
+    let mut group_height = 0u32;
+
+    for slice in &slices {
+        match groups.last_mut() {
+            Some(group) if group_height + slice.height() <= max_height => {
+                group.push(slice);
+                group_height += slice.height();
+            },
+            _ => {
+                groups.push(vec![slice]);
+                group_height = slice.height();
+            }
+        }
+    }
+
+    groups
+        .into_iter()
+        .filter_map(|group| {
+            let width = group.iter().map(|slice| slice.width()).max().unwrap_or(0u32);
+            let height = group.iter().map(|slice| slice.height()).sum::<u32>();
+
+            let mut stitched = image::RgbImage::from_pixel(width, height, image::Rgb([255u8, 255u8, 255u8]));
+            let mut y = 0u32;
+
+            for slice in group {
+                image::imageops::replace(&mut stitched, slice, (width - slice.width()) / 2u32, y);
+                y += slice.height();
+            }
+
+            let mut data = vec![];
+
+            match image::codecs::jpeg::JpegEncoder::new_with_quality(&mut data, 90u8).encode(&stitched, width, height, image::ColorType::Rgb8) {
+                Ok(()) => Some(data),
+                Err(error) => {
+                    warn!("encoding long strip image error: {}", error);
+                    None
+                }
+            }
+        })
+        .collect()
+}
\ No newline at end of file
@@ -0,0 +1,71 @@
+use crate::context::{GetOrFetch, GetOrFetchRef};
+
+type Cache<K, T> = tokio::sync::RwLock<std::collections::HashMap<K, std::sync::Arc<T>>>;
+
+// The keys being fetched. Whoever asks first fetches, the others wait for it and then read the cache,
+// so no lock is held while the fetch is running.
+pub struct InFlight<K: std::hash::Hash + Eq> {
+    keys: std::sync::Mutex<std::collections::HashMap<K, tokio::sync::watch::Receiver<()>>>
+}
+
+// Held by the one fetching the key, the waiting ones are woken up when it's dropped.
+struct Fetching<'a, K: std::hash::Hash + Eq> {
+    in_flight: &'a InFlight<K>,
+    key: K,
+    _done: tokio::sync::watch::Sender<()>
+}
+
+impl<K: std::hash::Hash + Eq> Default for InFlight<K> {
+    fn default() -> InFlight<K> {
+        InFlight { keys: std::sync::Mutex::default() }
+    }
+}
+
+impl<K: std::hash::Hash + Eq + Clone> InFlight<K> {
+    // Returns the cached value, or fetches it once however many ask for it at the same time. When the fetch fails
+    // the error goes to the one who fetched, and the next one waiting fetches again.
+    pub async fn get_or_fetch<T, E, F, Fut>(&self, cache: &Cache<K, T>, key: K, fetch: F) -> Result<GetOrFetchRef<T>, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<std::sync::Arc<T>, E>>
+    {
+        let fetching = loop {
+            if let Some(value) = cache.read().await.get(&key) {
+                return Ok(GetOrFetch::Cached(std::sync::Arc::downgrade(value)));
+            }
+
+            let mut waiting = {
+                let mut keys = self.keys.lock().unwrap();
+
+                match keys.get(&key) {
+                    Some(done) => done.clone(),
+                    None => {
+                        let (done, waiting) = tokio::sync::watch::channel(());
+                        keys.insert(key.clone(), waiting);
+
+                        break Fetching { in_flight: self, key: key.clone(), _done: done };
+                    }
+                }
+            };
+
+            while waiting.recv().await.is_some() {}
+        };
+
+        // It may have been cached between the first look and now.
+        if let Some(value) = cache.read().await.get(&key) {
+            return Ok(GetOrFetch::Cached(std::sync::Arc::downgrade(value)));
+        }
+
+        let value = fetch().await?;
+        cache.write().await.insert(key, value.clone());
+        drop(fetching);
+
+        Ok(GetOrFetch::Fetched(std::sync::Arc::downgrade(&value)))
+    }
+}
+
+impl<K: std::hash::Hash + Eq> Drop for Fetching<'_, K> {
+    fn drop(&mut self) {
+        self.in_flight.keys.lock().unwrap().remove(&self.key);
+    }
+}
\ No newline at end of file
@@ -189,7 +189,10 @@ pub enum Command {
     UnfollowManga(u64),
     MarkChapterRead(u64),
     MarkChapterUnread(u64),
-    Follows
+    Follows,
+    QueueStatus,
+    PinManga(u64, Option<String>),
+    UnpinManga(u64)
 }
 
 #[async_trait::async_trait]
@@ -236,7 +239,17 @@ impl ipc::IpcSend for Command {
                 stream.write_u8(ipc::COMMAND_MARK_CHAPTER_UNREAD).await?;
                 stream.write_u64(*id).await
             },
-            Command::Follows => stream.write_u8(ipc::COMMAND_FOLLOWS).await
+            Command::Follows => stream.write_u8(ipc::COMMAND_FOLLOWS).await,
+            Command::QueueStatus => stream.write_u8(ipc::COMMAND_QUEUE_STATUS).await,
+            Command::PinManga(id, chapters) => {
+                stream.write_u8(ipc::COMMAND_PIN_MANGA).await?;
+                stream.write_u64(*id).await?;
+                chapters.ipc_send(stream).await
+            },
+            Command::UnpinManga(id) => {
+                stream.write_u8(ipc::COMMAND_UNPIN_MANGA).await?;
+                stream.write_u64(*id).await
+            }
         }
     }
 }
@@ -262,6 +275,13 @@ impl ipc::IpcTryReceive for Command {
             ipc::COMMAND_MARK_CHAPTER_READ => Some(Command::MarkChapterRead(stream.read_u64().await?)),
             ipc::COMMAND_MARK_CHAPTER_UNREAD => Some(Command::MarkChapterUnread(stream.read_u64().await?)),
             ipc::COMMAND_FOLLOWS => Some(Command::Follows),
+            ipc::COMMAND_QUEUE_STATUS => Some(Command::QueueStatus),
+            ipc::COMMAND_PIN_MANGA => {
+                let id = stream.read_u64().await?;
+
+                Option::<String>::ipc_try_receive(stream).await?.map(|chapters| Command::PinManga(id, chapters))
+            },
+            ipc::COMMAND_UNPIN_MANGA => Some(Command::UnpinManga(stream.read_u64().await?)),
             byte => {
                 warn!("received unknown command byte: {}", byte);
                 None
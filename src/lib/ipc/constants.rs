@@ -10,6 +10,9 @@ pub const COMMAND_UNFOLLOW_MANGA: u8 = 8u8;
 pub const COMMAND_MARK_CHAPTER_READ: u8 = 9u8;
 pub const COMMAND_MARK_CHAPTER_UNREAD: u8 = 10u8;
 pub const COMMAND_FOLLOWS: u8 = 11u8;
+pub const COMMAND_QUEUE_STATUS: u8 = 12u8;
+pub const COMMAND_PIN_MANGA: u8 = 13u8;
+pub const COMMAND_UNPIN_MANGA: u8 = 14u8;
 
 pub const RESPONSE_KILL: u8 = 1u8;
 pub const RESPONSE_LOG_IN: u8 = 2u8;
@@ -22,6 +25,10 @@ pub const RESPONSE_UNFOLLOW_MANGA: u8 = 8u8;
 pub const RESPONSE_MARK_CHAPTER_READ: u8 = 9u8;
 pub const RESPONSE_MARK_CHAPTER_UNREAD: u8 = 10u8;
 pub const RESPONSE_FOLLOWS: u8 = 11u8;
+pub const RESPONSE_QUEUE_STATUS: u8 = 12u8;
+pub const RESPONSE_PIN_MANGA: u8 = 13u8;
+pub const RESPONSE_UNPIN_MANGA: u8 = 14u8;
+pub const RESPONSE_PIN_PROGRESS: u8 = 15u8;
 
 pub const RESULT_OK: u8 = 0u8;
 pub const RESULT_ERR: u8 = 1u8;
@@ -34,4 +41,9 @@ pub const MDLIST_STATUS_COMPLETED: u8 = 1u8;
 pub const MDLIST_STATUS_ON_HOLD: u8 = 2u8;
 pub const MDLIST_STATUS_PLAN_TO_READ: u8 = 3u8;
 pub const MDLIST_STATUS_DROPPED: u8 = 4u8;
-pub const MDLIST_STATUS_RE_READING: u8 = 5u8;
\ No newline at end of file
+pub const MDLIST_STATUS_RE_READING: u8 = 5u8;
+
+pub const PRIORITY_INTERACTIVE: u8 = 0u8;
+pub const PRIORITY_READDIR: u8 = 1u8;
+pub const PRIORITY_PREFETCH: u8 = 2u8;
+pub const PRIORITY_BACKGROUND: u8 = 3u8;
\ No newline at end of file
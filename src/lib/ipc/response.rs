@@ -1,5 +1,6 @@
 use crate::ipc;
 use crate::api;
+use crate::scheduler;
 use ipc::{IpcReceive};
 use tokio::io::{AsyncWriteExt, AsyncReadExt};
 
@@ -180,6 +181,39 @@ impl ipc::IpcTryReceive for api::MDListEntry {
     }
 }
 
+#[async_trait::async_trait]
+impl ipc::IpcSend for scheduler::QueueStatus {
+    async fn ipc_send<W: tokio::io::AsyncWrite + Unpin + Send>(&self, stream: &mut W) -> std::io::Result<()> {
+        match self.priority {
+            scheduler::Priority::Interactive => stream.write_u8(ipc::PRIORITY_INTERACTIVE).await?,
+            scheduler::Priority::Readdir => stream.write_u8(ipc::PRIORITY_READDIR).await?,
+            scheduler::Priority::Prefetch => stream.write_u8(ipc::PRIORITY_PREFETCH).await?,
+            scheduler::Priority::Background => stream.write_u8(ipc::PRIORITY_BACKGROUND).await?
+        };
+
+        stream.write_u64(self.queued).await?;
+        stream.write_u64(self.active).await
+    }
+}
+
+#[async_trait::async_trait]
+impl ipc::IpcTryReceive for scheduler::QueueStatus {
+    async fn ipc_try_receive<R: tokio::io::AsyncRead + Unpin + Send>(stream: &mut R) -> std::io::Result<Option<scheduler::QueueStatus>> {
+        let priority = match u8::ipc_receive(stream).await? {
+            ipc::PRIORITY_INTERACTIVE => scheduler::Priority::Interactive,
+            ipc::PRIORITY_READDIR => scheduler::Priority::Readdir,
+            ipc::PRIORITY_PREFETCH => scheduler::Priority::Prefetch,
+            ipc::PRIORITY_BACKGROUND => scheduler::Priority::Background,
+            _ => return Ok(None)
+        };
+
+        let queued = stream.read_u64().await?;
+        let active = stream.read_u64().await?;
+
+        Ok(Some(scheduler::QueueStatus { priority, queued, active }))
+    }
+}
+
 #[derive(Debug)]
 pub enum Response {
     Kill,
@@ -192,7 +226,12 @@ pub enum Response {
     UnfollowManga(Result<(), String>),
     MarkChapterRead(Result<(), String>),
     MarkChapterUnread(Result<(), String>),
-    Follows(Result<Vec<api::FollowsEntry>, String>)
+    Follows(Result<Vec<api::FollowsEntry>, String>),
+    QueueStatus(Vec<scheduler::QueueStatus>),
+    PinManga(Result<(), String>),
+    UnpinManga(Result<(), String>),
+    // Chapters pinned so far out of all those being pinned, sent before the final `PinManga`.
+    PinProgress(u64, u64)
 }
 
 #[async_trait::async_trait]
@@ -241,6 +280,23 @@ impl ipc::IpcSend for Response {
             Response::Follows(follows) => {
                 stream.write_u8(ipc::RESPONSE_FOLLOWS).await?;
                 follows.ipc_send(stream).await
+            },
+            Response::QueueStatus(queue_status) => {
+                stream.write_u8(ipc::RESPONSE_QUEUE_STATUS).await?;
+                queue_status.ipc_send(stream).await
+            },
+            Response::PinManga(pinmanga) => {
+                stream.write_u8(ipc::RESPONSE_PIN_MANGA).await?;
+                pinmanga.ipc_send(stream).await
+            },
+            Response::UnpinManga(unpinmanga) => {
+                stream.write_u8(ipc::RESPONSE_UNPIN_MANGA).await?;
+                unpinmanga.ipc_send(stream).await
+            },
+            Response::PinProgress(pinned, total) => {
+                stream.write_u8(ipc::RESPONSE_PIN_PROGRESS).await?;
+                stream.write_u64(*pinned).await?;
+                stream.write_u64(*total).await
             }
         }
     }
@@ -261,6 +317,10 @@ impl ipc::IpcTryReceive for Response {
             ipc::RESPONSE_MARK_CHAPTER_READ => Result::<(), String>::ipc_try_receive(stream).await?.map(Response::MarkChapterRead),
             ipc::RESPONSE_MARK_CHAPTER_UNREAD => Result::<(), String>::ipc_try_receive(stream).await?.map(Response::MarkChapterUnread),
             ipc::RESPONSE_FOLLOWS => Result::<Vec<api::FollowsEntry>, String>::ipc_try_receive(stream).await?.map(Response::Follows),
+            ipc::RESPONSE_QUEUE_STATUS => Vec::<scheduler::QueueStatus>::ipc_try_receive(stream).await?.map(Response::QueueStatus),
+            ipc::RESPONSE_PIN_MANGA => Result::<(), String>::ipc_try_receive(stream).await?.map(Response::PinManga),
+            ipc::RESPONSE_UNPIN_MANGA => Result::<(), String>::ipc_try_receive(stream).await?.map(Response::UnpinManga),
+            ipc::RESPONSE_PIN_PROGRESS => Some(Response::PinProgress(stream.read_u64().await?, stream.read_u64().await?)),
             byte => {
                 warn!("received unknown response byte: {}", byte);
                 None
@@ -5,6 +5,9 @@ pub mod api;
 pub mod ipc;
 pub mod cfg;
 pub mod fs;
+pub mod scheduler;
+pub mod in_flight;
+pub mod store;
 
 pub mod context;
 pub use api::MangaDexAPI;
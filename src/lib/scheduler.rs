@@ -0,0 +1,206 @@
+// Download classes, most urgent first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Priority {
+    // A page the user is waiting on.
+    Interactive,
+    // Pages and covers of a directory being listed.
+    Readdir,
+    // Chapters read ahead.
+    Prefetch,
+    // Anything nobody is waiting on.
+    Background
+}
+
+pub const PRIORITIES: [Priority; 4] = [Priority::Interactive, Priority::Readdir, Priority::Prefetch, Priority::Background];
+
+impl Priority {
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    // Prefetch and background downloads leave a slot free for the downloads that may come.
+    fn is_low(self) -> bool {
+        match self {
+            Priority::Interactive | Priority::Readdir => false,
+            Priority::Prefetch | Priority::Background => true
+        }
+    }
+}
+
+impl std::fmt::Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Priority::Interactive => write!(f, "interactive"),
+            Priority::Readdir => write!(f, "readdir"),
+            Priority::Prefetch => write!(f, "prefetch"),
+            Priority::Background => write!(f, "background")
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct QueueStatus {
+    pub priority: Priority,
+    pub queued: u64,
+    pub active: u64
+}
+
+#[derive(Default)]
+struct State {
+    queued: [usize; 4],
+    active: [usize; 4],
+    urls: std::collections::HashSet<reqwest::Url>,
+    // Queued downloads by URL, and the URLs someone is now blocked on.
+    waiting: std::collections::HashMap<reqwest::Url, usize>,
+    promoted: std::collections::HashSet<reqwest::Url>,
+    // Bumped on every cancellation of a chapter, downloads queued before that give up.
+    generations: std::collections::HashMap<u64, u64>
+}
+
+// Schedules every download of the daemon. A download starts once there is a free slot and no download of a more
+// urgent class is queued. The same URL is never downloaded twice at once: the second download waits for the first
+// one and then finds it cached.
+pub struct Scheduler {
+    downloads: usize,
+    reserved: usize,
+    state: std::sync::Mutex<State>,
+    changed: tokio::sync::watch::Sender<()>,
+    watcher: tokio::sync::watch::Receiver<()>
+}
+
+// A queued download, counted until it starts or is given up.
+struct Waiting<'a> {
+    scheduler: &'a Scheduler,
+    priority: Priority,
+    url: &'a reqwest::Url
+}
+
+// A running download, the slot is given back when it's dropped.
+pub struct Ticket {
+    scheduler: std::sync::Arc<Scheduler>,
+    priority: Priority,
+    url: reqwest::Url
+}
+
+impl Scheduler {
+    pub fn new(downloads: usize) -> Scheduler {
+        let downloads = std::cmp::max(downloads, 1usize);
+        let (changed, watcher) = tokio::sync::watch::channel(());
+
+        Scheduler {
+            downloads,
+            reserved: std::cmp::min(downloads - 1usize, 1usize),
+            state: std::sync::Mutex::default(),
+            changed,
+            watcher
+        }
+    }
+
+    // Waits for a slot to download the URL in. Returns `None` if the chapter is cancelled in the meantime.
+    pub async fn acquire(self: &std::sync::Arc<Scheduler>, priority: Priority, chapter_id: Option<u64>, url: &reqwest::Url) -> Option<Ticket> {
+        let mut watcher = self.watcher.clone();
+
+        let generation = {
+            let mut state = self.state.lock().unwrap();
+            state.queued[priority.index()] += 1usize;
+            *state.waiting.entry(url.clone()).or_default() += 1usize;
+
+            chapter_id.and_then(|chapter_id| state.generations.get(&chapter_id).cloned())
+        };
+
+        let mut waiting = Waiting { scheduler: self, priority, url };
+
+        loop {
+            {
+                let mut state = self.state.lock().unwrap();
+
+                if chapter_id.and_then(|chapter_id| state.generations.get(&chapter_id).cloned()) != generation {
+                    debug!("download of {} cancelled", url);
+                    return None;
+                }
+
+                if waiting.priority != Priority::Interactive && state.promoted.contains(url) {
+                    debug!("download of {} promoted from {}", url, waiting.priority);
+                    state.queued[waiting.priority.index()] -= 1usize;
+                    state.queued[Priority::Interactive.index()] += 1usize;
+                    waiting.priority = Priority::Interactive;
+                }
+
+                let priority = waiting.priority;
+
+                let urgent_queued = state.queued[..priority.index()].iter().any(|queued| *queued > 0usize);
+                let free = self.downloads - state.active.iter().sum::<usize>();
+                let reserved = if priority.is_low() { self.reserved } else { 0usize };
+
+                if !urgent_queued && free > reserved && !state.urls.contains(url) {
+                    state.active[priority.index()] += 1usize;
+                    state.urls.insert(url.clone());
+
+                    return Some(Ticket { scheduler: self.clone(), priority, url: url.clone() });
+                }
+            }
+
+            watcher.recv().await;
+        }
+    }
+
+    // Moves the queued downloads of the URL to the interactive class, someone is blocked on them.
+    pub fn promote(&self, url: &reqwest::Url) {
+        let mut state = self.state.lock().unwrap();
+
+        if state.waiting.contains_key(url) {
+            state.promoted.insert(url.clone());
+            drop(state);
+
+            self.changed.broadcast(()).ok();
+        }
+    }
+
+    // Gives up every download of the chapter that hasn't started yet.
+    pub fn cancel(&self, chapter_id: u64) {
+        *self.state.lock().unwrap().generations.entry(chapter_id).or_default() += 1u64;
+        self.changed.broadcast(()).ok();
+    }
+
+    pub fn status(&self) -> Vec<QueueStatus> {
+        let state = self.state.lock().unwrap();
+
+        PRIORITIES.iter().map(|priority| QueueStatus {
+            priority: *priority,
+            queued: state.queued[priority.index()] as u64,
+            active: state.active[priority.index()] as u64
+        }).collect()
+    }
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        {
+            let mut state = self.scheduler.state.lock().unwrap();
+            state.queued[self.priority.index()] -= 1usize;
+
+            if let Some(waiting) = state.waiting.get_mut(self.url) {
+                *waiting -= 1usize;
+
+                if *waiting == 0usize {
+                    state.waiting.remove(self.url);
+                    state.promoted.remove(self.url);
+                }
+            }
+        }
+
+        self.scheduler.changed.broadcast(()).ok();
+    }
+}
+
+impl Drop for Ticket {
+    fn drop(&mut self) {
+        {
+            let mut state = self.scheduler.state.lock().unwrap();
+            state.active[self.priority.index()] -= 1usize;
+            state.urls.remove(&self.url);
+        }
+
+        self.scheduler.changed.broadcast(()).ok();
+    }
+}
\ No newline at end of file
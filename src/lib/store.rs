@@ -0,0 +1,203 @@
+use crate::api;
+use crate::fs;
+
+const PINS_FILE_NAME: &str = "pins.json";
+
+#[derive(Debug)]
+pub enum PinError {
+    Request(reqwest::Error),
+    IO(std::io::Error),
+    // A page didn't finish downloading.
+    Incomplete
+}
+
+// Parses "12" or "1-20" into the range of chapter numbers it covers. Both bounds are required, and the first can't
+// be past the second.
+pub fn parse_chapter_range(range: &str) -> Option<(f64, f64)> {
+    let mut bounds = range.split('-').map(|bound| bound.trim().parse::<f64>());
+
+    let from = bounds.next()?.ok()?;
+    let to = match bounds.next() {
+        Some(to) => to.ok()?,
+        None => from
+    };
+
+    match bounds.next() {
+        None if from <= to => Some((from, to)),
+        _ => None
+    }
+}
+
+// Pinned manga, by id, with their pinned chapters.
+type Pins = std::collections::HashMap<u64, Vec<u64>>;
+
+// Keeps pinned manga on the disk: the API responses of the manga and its pinned chapters, their pages and the cover.
+// They are read back when MangaDex can't be reached.
+pub struct Store {
+    path: std::path::PathBuf,
+    pins: tokio::sync::RwLock<Pins>
+}
+
+async fn write(path: std::path::PathBuf, data: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+
+    tokio::fs::write(path, data).await
+}
+
+async fn remove(path: std::path::PathBuf) {
+    if let Err(error) = tokio::fs::remove_file(&path).await {
+        debug!("removing {} error: {}", path.display(), error);
+    }
+}
+
+impl Store {
+    pub fn open(path: std::path::PathBuf) -> Store {
+        let pins = match std::fs::read(path.join(PINS_FILE_NAME)) {
+            Ok(contents) => serde_json::from_slice(&contents).unwrap_or_else(|error| {
+                warn!("invalid pins file, nothing is pinned: {}", error);
+                Pins::default()
+            }),
+            Err(_) => Pins::default()
+        };
+
+        Store { path, pins: tokio::sync::RwLock::new(pins) }
+    }
+
+    fn manga_path(&self, id: u64) -> std::path::PathBuf {
+        self.path.join("manga").join(format!("{}.json", id))
+    }
+
+    fn chapter_path(&self, id: u64) -> std::path::PathBuf {
+        self.path.join("chapter").join(format!("{}.json", id))
+    }
+
+    // Pages and covers are stored under their host and path, "files/s2.mangadex.org/data/<hash>/x1.png".
+    fn file_path(&self, url: &reqwest::Url) -> std::path::PathBuf {
+        let mut path = self.path.join("files");
+        path.push(sanitize_filename::sanitize(url.host_str().unwrap_or_default()));
+
+        for segment in url.path_segments().into_iter().flatten() {
+            path.push(sanitize_filename::sanitize(segment));
+        }
+
+        path
+    }
+
+    pub async fn is_pinned(&self, manga_id: u64) -> bool {
+        self.pins.read().await.contains_key(&manga_id)
+    }
+
+    pub async fn pinned(&self) -> Vec<u64> {
+        self.pins.read().await.keys().cloned().collect()
+    }
+
+    pub async fn load_manga(&self, id: u64) -> Option<api::Manga> {
+        api::Manga::from_raw(&tokio::fs::read(self.manga_path(id)).await.ok()?)
+    }
+
+    pub async fn load_chapter(&self, id: u64) -> Option<api::Chapter> {
+        api::Chapter::from_raw(&tokio::fs::read(self.chapter_path(id)).await.ok()?)
+    }
+
+    pub async fn load_file(&self, url: &reqwest::Url) -> Option<Vec<u8>> {
+        tokio::fs::read(self.file_path(url)).await.ok()
+    }
+
+    pub async fn save_manga(&self, id: u64, raw: &[u8]) -> std::io::Result<()> {
+        write(self.manga_path(id), raw).await
+    }
+
+    pub async fn save_chapter(&self, id: u64, raw: &[u8]) -> std::io::Result<()> {
+        write(self.chapter_path(id), raw).await
+    }
+
+    pub async fn save_file(&self, url: &reqwest::Url, data: &[u8]) -> std::io::Result<()> {
+        write(self.file_path(url), data).await
+    }
+
+    // Adds the chapters to the pinned ones of the manga.
+    pub async fn pin(&self, manga_id: u64, chapter_ids: &[u64]) -> std::io::Result<()> {
+        let mut pins = self.pins.write().await;
+
+        let pinned = pins.entry(manga_id).or_default();
+        for chapter_id in chapter_ids {
+            if !pinned.contains(chapter_id) {
+                pinned.push(*chapter_id);
+            }
+        }
+
+        self.save_pins(&pins).await
+    }
+
+    // Removes the manga from the pinned ones along with everything stored for it.
+    pub async fn unpin(&self, manga_id: u64) -> std::io::Result<bool> {
+        let mut pins = self.pins.write().await;
+
+        let chapter_ids = match pins.remove(&manga_id) {
+            Some(chapter_ids) => chapter_ids,
+            None => return Ok(false)
+        };
+
+        self.save_pins(&pins).await?;
+
+        for chapter_id in chapter_ids {
+            if let Some(chapter) = self.load_chapter(chapter_id).await.map(|chapter_api| fs::entry::Chapter::new(chapter_id, chapter_api)) {
+                if let fs::entry::ChapterPages::Hosted(hosted) = &chapter.pages {
+                    for page in &hosted.pages {
+                        if let Ok(url) = hosted.url.join(page) {
+                            remove(self.file_path(&url)).await;
+                        }
+                    }
+                }
+            }
+
+            remove(self.chapter_path(chapter_id)).await;
+        }
+
+        if let Some(manga) = self.load_manga(manga_id).await.map(|manga_api| fs::entry::Manga::new(manga_id, manga_api)) {
+            if let Some(url) = &manga.cover {
+                remove(self.file_path(url)).await;
+            }
+        }
+
+        remove(self.manga_path(manga_id)).await;
+
+        Ok(true)
+    }
+
+    // Written next to the pins file and renamed over it, so a crash halfway through doesn't lose every pin.
+    async fn save_pins(&self, pins: &Pins) -> std::io::Result<()> {
+        let contents = serde_json::to_vec(pins).map_err(std::io::Error::from)?;
+        let temporary = self.path.join(format!("{}.tmp", PINS_FILE_NAME));
+
+        write(temporary.clone(), &contents).await?;
+        tokio::fs::rename(temporary, self.path.join(PINS_FILE_NAME)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chapter_ranges() {
+        assert_eq!(parse_chapter_range("12"), Some((12f64, 12f64)));
+        assert_eq!(parse_chapter_range("1-20"), Some((1f64, 20f64)));
+        assert_eq!(parse_chapter_range(" 1 - 20 "), Some((1f64, 20f64)));
+        assert_eq!(parse_chapter_range("10.5-11"), Some((10.5f64, 11f64)));
+        assert_eq!(parse_chapter_range("3-3"), Some((3f64, 3f64)));
+    }
+
+    #[test]
+    fn invalid_chapter_ranges() {
+        assert_eq!(parse_chapter_range(""), None);
+        assert_eq!(parse_chapter_range("1-"), None);
+        assert_eq!(parse_chapter_range("-5"), None);
+        assert_eq!(parse_chapter_range("1-2-3"), None);
+        assert_eq!(parse_chapter_range("20-1"), None);
+        assert_eq!(parse_chapter_range("one-two"), None);
+        assert_eq!(parse_chapter_range("NaN"), None);
+    }
+}
\ No newline at end of file